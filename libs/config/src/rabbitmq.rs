@@ -1,6 +1,8 @@
 use lapin::{Channel, Connection, ConnectionProperties};
 use std::sync::Arc;
 
+pub mod topology;
+
 pub async fn get_connection() -> Result<Arc<Connection>, lapin::Error> {
     let user = std::env::var("RABBITMQ_USER").expect("RABBITMQ_USER not set");
     let pass = std::env::var("RABBITMQ_PASSWORD").expect("RABBITMQ_PASSWORD not set");
@@ -17,7 +19,7 @@ pub async fn get_connection() -> Result<Arc<Connection>, lapin::Error> {
 pub async fn create_channel() -> Result<Channel, lapin::Error> {
     let conn = get_connection().await?;
 
-    let channel = (&conn).create_channel().await?;
+    let channel = conn.create_channel().await?;
 
     Ok(channel)
 }
@@ -0,0 +1,248 @@
+use contracts::notification::{NotificationChannel, TrackingEventMsgType};
+use lapin::options::{
+    ExchangeDeclareOptions, QueueBindOptions, QueueDeclareOptions, QueueDeleteOptions,
+};
+use lapin::protocol::{AMQPErrorKind, AMQPSoftError};
+use lapin::types::{AMQPValue, FieldTable};
+use lapin::{Channel, Connection, ErrorKind, ExchangeKind};
+
+/// topic exchange the tracking service publishes notification events to
+pub const NOTIFICATION_EXCHANGE: &str = "notification.events";
+/// where messages rejected by a notification consumer end up, routed by queue name
+pub const NOTIFICATION_DLX: &str = "notification.dlx";
+
+/// the exchanges, queues and bindings both services rely on. declaring is idempotent,
+/// so each service declares everything on startup and whichever starts first on a
+/// fresh broker sets it up.
+///
+/// work queues created before they dead-lettered to `<queue>.dlq` have no
+/// `x-dead-letter-*` arguments and rabbitmq refuses to redeclare them. drain and delete
+/// those queues by hand before deploying, or start one service with
+/// `NOTIFICATION_RECREATE_QUEUES=true` to have empty ones deleted and recreated
+#[derive(Clone, Debug)]
+pub struct NotificationTopology {
+    queues: Vec<(NotificationChannel, String)>,
+    /// how long a failed message waits in `<queue>.retry` before it's redelivered.
+    /// rabbitmq refuses to redeclare a queue with a different ttl, delete the retry
+    /// queues when changing it
+    pub retry_delay_ms: u32,
+}
+
+impl NotificationTopology {
+    /// queue names come from `WA_QUEUE`, `TELE_QUEUE` and `EMAIL_QUEUE`, the retry
+    /// delay from `NOTIFICATION_RETRY_DELAY_MS`
+    pub fn from_env() -> Self {
+        let queue = |var: &str, default: &str| std::env::var(var).unwrap_or(default.to_string());
+
+        Self {
+            queues: vec![
+                (
+                    NotificationChannel::Whatsapp,
+                    queue("WA_QUEUE", "notification.whatsapp"),
+                ),
+                (
+                    NotificationChannel::Telegram,
+                    queue("TELE_QUEUE", "notification.telegram"),
+                ),
+                (
+                    NotificationChannel::Email,
+                    queue("EMAIL_QUEUE", "notification.email"),
+                ),
+            ],
+            retry_delay_ms: std::env::var("NOTIFICATION_RETRY_DELAY_MS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(30_000),
+        }
+    }
+
+    pub fn queue_for(&self, channel: &NotificationChannel) -> &str {
+        self.queues
+            .iter()
+            .find(|(ch, _)| ch == channel)
+            .map(|(_, queue)| queue.as_str())
+            .expect("every channel has a queue")
+    }
+
+    pub async fn declare(&self, channel: &Channel) -> Result<(), lapin::Error> {
+        let durable = ExchangeDeclareOptions {
+            durable: true,
+            ..ExchangeDeclareOptions::default()
+        };
+
+        channel
+            .exchange_declare(
+                NOTIFICATION_EXCHANGE,
+                ExchangeKind::Topic,
+                durable,
+                FieldTable::default(),
+            )
+            .await?;
+        channel
+            .exchange_declare(
+                NOTIFICATION_DLX,
+                ExchangeKind::Direct,
+                durable,
+                FieldTable::default(),
+            )
+            .await?;
+
+        for (ch, queue) in &self.queues {
+            self.declare_queue(channel, queue).await?;
+
+            for event_type in TrackingEventMsgType::ALL {
+                let routing_key = format!(
+                    "notification.{}.{}",
+                    event_type.routing_name(),
+                    ch.routing_name()
+                );
+
+                channel
+                    .queue_bind(
+                        queue,
+                        NOTIFICATION_EXCHANGE,
+                        &routing_key,
+                        QueueBindOptions::default(),
+                        FieldTable::default(),
+                    )
+                    .await?;
+            }
+        }
+
+        tracing::info!("declared rabbitmq notification topology");
+
+        Ok(())
+    }
+
+    /// the queue dead-letters to `<queue>.dlq` through the dlx. `<queue>.retry` has no
+    /// consumer, its messages expire back into the queue through the default exchange
+    async fn declare_queue(&self, channel: &Channel, queue: &str) -> Result<(), lapin::Error> {
+        let durable = QueueDeclareOptions {
+            durable: true,
+            ..QueueDeclareOptions::default()
+        };
+        let dlq = dead_letter_queue(queue);
+
+        channel
+            .queue_declare(&dlq, durable, FieldTable::default())
+            .await?;
+        channel
+            .queue_bind(
+                &dlq,
+                NOTIFICATION_DLX,
+                queue,
+                QueueBindOptions::default(),
+                FieldTable::default(),
+            )
+            .await?;
+
+        let mut args = FieldTable::default();
+        args.insert(
+            "x-dead-letter-exchange".into(),
+            AMQPValue::LongString(NOTIFICATION_DLX.into()),
+        );
+        args.insert(
+            "x-dead-letter-routing-key".into(),
+            AMQPValue::LongString(queue.into()),
+        );
+
+        migrate_work_queue(queue, durable, &args).await?;
+        channel.queue_declare(queue, durable, args).await?;
+
+        let mut args = FieldTable::default();
+        args.insert(
+            "x-message-ttl".into(),
+            AMQPValue::LongUInt(self.retry_delay_ms),
+        );
+        args.insert(
+            "x-dead-letter-exchange".into(),
+            AMQPValue::LongString("".into()),
+        );
+        args.insert(
+            "x-dead-letter-routing-key".into(),
+            AMQPValue::LongString(queue.into()),
+        );
+
+        channel
+            .queue_declare(&retry_queue(queue), durable, args)
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// tries the work queue declare on its own connection, a refused declare closes the
+/// channel it ran on and would take the caller's channel down with it
+async fn migrate_work_queue(
+    queue: &str,
+    options: QueueDeclareOptions,
+    args: &FieldTable,
+) -> Result<(), lapin::Error> {
+    let conn = super::get_connection().await?;
+    let result = try_migrate_work_queue(&conn, queue, options, args).await;
+    let _ = conn.close(200, "OK").await;
+
+    result
+}
+
+async fn try_migrate_work_queue(
+    conn: &Connection,
+    queue: &str,
+    options: QueueDeclareOptions,
+    args: &FieldTable,
+) -> Result<(), lapin::Error> {
+    let probe = conn.create_channel().await?;
+    let Err(e) = probe.queue_declare(queue, options, args.clone()).await else {
+        return Ok(());
+    };
+    if !is_precondition_failed(&e) {
+        return Err(e);
+    }
+
+    let recreate = std::env::var("NOTIFICATION_RECREATE_QUEUES")
+        .map(|v| v == "true")
+        .unwrap_or(false);
+    if !recreate {
+        tracing::error!(
+            queue,
+            "queue exists without dead-letter arguments, drain and delete it or set NOTIFICATION_RECREATE_QUEUES=true"
+        );
+        return Err(e);
+    }
+
+    // if_empty so a queue still holding messages is never dropped, the broker refuses
+    // the delete and startup fails until it's drained
+    conn.create_channel()
+        .await?
+        .queue_delete(
+            queue,
+            QueueDeleteOptions {
+                if_empty: true,
+                ..QueueDeleteOptions::default()
+            },
+        )
+        .await?;
+
+    tracing::warn!(
+        queue,
+        "deleted queue without dead-letter arguments to recreate it"
+    );
+
+    Ok(())
+}
+
+fn is_precondition_failed(e: &lapin::Error) -> bool {
+    matches!(
+        e.kind(),
+        ErrorKind::ProtocolError(err)
+            if *err.kind() == AMQPErrorKind::Soft(AMQPSoftError::PRECONDITIONFAILED)
+    )
+}
+
+pub fn dead_letter_queue(queue: &str) -> String {
+    format!("{}.dlq", queue)
+}
+
+pub fn retry_queue(queue: &str) -> String {
+    format!("{}.retry", queue)
+}
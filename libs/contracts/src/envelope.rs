@@ -0,0 +1,49 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// a payload that can travel inside an `EventEnvelope`
+pub trait Event {
+    /// bumped only for breaking changes, new optional fields keep the version since
+    /// older consumers ignore fields they don't know. roll consumers out first when
+    /// bumping it
+    const SCHEMA_VERSION: u16;
+
+    /// e.g. `tracking.added`
+    fn event_type(&self) -> &'static str;
+}
+
+/// metadata wrapped around every message put on the bus, `schema_version` tells the
+/// consumer how to read `data`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventEnvelope<T> {
+    pub schema_version: u16,
+    pub event_type: String,
+    pub produced_at: DateTime<Utc>,
+    /// shared by every message caused by the same request or status change
+    pub correlation_id: Uuid,
+    /// name of the service that published the message
+    pub producer: String,
+    pub data: T,
+}
+
+impl<T: Event> EventEnvelope<T> {
+    pub fn wrap(data: T, producer: &str, correlation_id: Uuid) -> Self {
+        Self {
+            schema_version: T::SCHEMA_VERSION,
+            event_type: data.event_type().to_string(),
+            produced_at: Utc::now(),
+            correlation_id,
+            producer: producer.to_string(),
+            data,
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum DecodeError {
+    #[error("malformed message: {0}")]
+    Malformed(#[from] serde_json::Error),
+    #[error("unsupported schema version {0}")]
+    UnsupportedVersion(u16),
+}
@@ -0,0 +1,5 @@
+//! message types shared by the services talking over rabbitmq. the producer and
+//! consumer both depend on this crate so the wire format is defined once
+
+pub mod envelope;
+pub mod notification;
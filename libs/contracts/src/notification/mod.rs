@@ -0,0 +1,148 @@
+//! each wire version lives in its own module and stays frozen once released, a
+//! breaking change gets a new module instead of editing the old one. the types at
+//! this level are always the current version
+//!
+//! producers pick what they publish with `EVENT_SCHEMA_VERSION`, `0` sends the bare
+//! v1 message older consumers expect. rolling out the envelope, or rolling it back:
+//!
+//! 1. deploy the producers with `EVENT_SCHEMA_VERSION=0`
+//! 2. deploy the consumers, they read both the bare message and the envelope
+//! 3. unset `EVENT_SCHEMA_VERSION` on the producers so they send the envelope
+//!
+//! rolling a consumer back to a release without the envelope goes the other way,
+//! producers back to `0` first. later version bumps follow the same order, consumers
+//! that read the new version before producers that send it
+
+pub mod v1;
+
+pub use v1::*;
+
+use crate::envelope::{DecodeError, EventEnvelope};
+use chrono::Utc;
+use serde_json::Value;
+
+/// version reported for messages published before the envelope existed
+pub const LEGACY_SCHEMA_VERSION: u16 = 0;
+
+/// the wire form of a tracking event in `schema_version`, `LEGACY_SCHEMA_VERSION`
+/// drops the envelope and leaves the bare v1 message
+pub fn encode_tracking_event(
+    envelope: &EventEnvelope<TrackingEventMsg>,
+    schema_version: u16,
+) -> Result<Value, serde_json::Error> {
+    match schema_version {
+        LEGACY_SCHEMA_VERSION => serde_json::to_value(&envelope.data),
+        _ => serde_json::to_value(envelope),
+    }
+}
+
+/// reads a tracking event off the wire, whichever version the producer speaks.
+/// un-enveloped messages from older producers are the bare v1 message, they get an
+/// envelope with `LEGACY_SCHEMA_VERSION`, the message id as correlation id and the
+/// time they were decoded as `produced_at`
+pub fn decode_tracking_event(bytes: &[u8]) -> Result<EventEnvelope<TrackingEventMsg>, DecodeError> {
+    let value: Value = serde_json::from_slice(bytes)?;
+
+    let Some(version) = value.get("schema_version") else {
+        let msg: v1::TrackingEventMsg = serde_json::from_value(value)?;
+        return Ok(EventEnvelope {
+            schema_version: LEGACY_SCHEMA_VERSION,
+            event_type: msg.event_type.name().to_string(),
+            produced_at: Utc::now(),
+            correlation_id: msg.message_id,
+            producer: "unknown".to_string(),
+            data: msg,
+        });
+    };
+
+    match serde_json::from_value::<u16>(version.clone())? {
+        1 => Ok(serde_json::from_value::<EventEnvelope<v1::TrackingEventMsg>>(value)?),
+        other => Err(DecodeError::UnsupportedVersion(other)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::envelope::Event;
+    use serde_json::json;
+    use uuid::Uuid;
+
+    fn msg() -> TrackingEventMsg {
+        TrackingEventMsg {
+            message_id: Uuid::new_v4(),
+            event_type: TrackingEventMsgType::TrackingStatusUpdated,
+            channel: NotificationChannel::Email,
+            user_id: Uuid::new_v4(),
+            recipient: "user@example.com".to_string(),
+            template_code: "TRACKING_STATUS".to_string(),
+            payload: TrackingMsgPayload {
+                waybill_id: "AWB1".to_string(),
+                status: "delivered".to_string(),
+                courier: "jne".to_string(),
+            },
+        }
+    }
+
+    #[test]
+    fn decodes_the_legacy_bare_message() {
+        let msg = msg();
+        let bytes = serde_json::to_vec(&msg).unwrap();
+
+        let event = decode_tracking_event(&bytes).unwrap();
+
+        assert_eq!(event.schema_version, LEGACY_SCHEMA_VERSION);
+        assert_eq!(event.event_type, msg.event_type.name());
+        assert_eq!(event.correlation_id, msg.message_id);
+        assert_eq!(event.data.message_id, msg.message_id);
+    }
+
+    #[test]
+    fn decodes_the_v1_envelope() {
+        let correlation_id = Uuid::new_v4();
+        let envelope = EventEnvelope::wrap(msg(), "tracking-service", correlation_id);
+        let bytes = serde_json::to_vec(&envelope).unwrap();
+
+        let event = decode_tracking_event(&bytes).unwrap();
+
+        assert_eq!(event.schema_version, TrackingEventMsg::SCHEMA_VERSION);
+        assert_eq!(event.correlation_id, correlation_id);
+        assert_eq!(event.producer, "tracking-service");
+        assert_eq!(event.data.message_id, envelope.data.message_id);
+    }
+
+    #[test]
+    fn rejects_an_unknown_version() {
+        let envelope = EventEnvelope::wrap(msg(), "tracking-service", Uuid::new_v4());
+        let mut value = serde_json::to_value(&envelope).unwrap();
+        value["schema_version"] = json!(2);
+
+        let err = decode_tracking_event(&serde_json::to_vec(&value).unwrap()).unwrap_err();
+
+        assert!(matches!(err, DecodeError::UnsupportedVersion(2)));
+    }
+
+    #[test]
+    fn rejects_malformed_messages() {
+        for bytes in [
+            &b"not json"[..],
+            br#"{"schema_version": "one"}"#,
+            br#"{"foo": 1}"#,
+        ] {
+            let err = decode_tracking_event(bytes).unwrap_err();
+            assert!(matches!(err, DecodeError::Malformed(_)));
+        }
+    }
+
+    #[test]
+    fn legacy_encoding_round_trips_as_the_bare_message() {
+        let envelope = EventEnvelope::wrap(msg(), "tracking-service", Uuid::new_v4());
+
+        let value = encode_tracking_event(&envelope, LEGACY_SCHEMA_VERSION).unwrap();
+        assert!(value.get("schema_version").is_none());
+
+        let event = decode_tracking_event(&serde_json::to_vec(&value).unwrap()).unwrap();
+        assert_eq!(event.schema_version, LEGACY_SCHEMA_VERSION);
+        assert_eq!(event.data.message_id, envelope.data.message_id);
+    }
+}
@@ -0,0 +1,108 @@
+use crate::envelope::Event;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use uuid::Uuid;
+
+/// mirrors the `notification_channel` postgres enum
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, Hash)]
+#[serde(rename_all = "UPPERCASE")]
+#[cfg_attr(feature = "sqlx", derive(sqlx::Type))]
+#[cfg_attr(
+    feature = "sqlx",
+    sqlx(type_name = "notification_channel", rename_all = "UPPERCASE")
+)]
+pub enum NotificationChannel {
+    Whatsapp,
+    Email,
+    Telegram,
+}
+
+impl NotificationChannel {
+    pub const ALL: [NotificationChannel; 3] = [
+        NotificationChannel::Whatsapp,
+        NotificationChannel::Email,
+        NotificationChannel::Telegram,
+    ];
+
+    pub fn routing_name(&self) -> &'static str {
+        match self {
+            NotificationChannel::Whatsapp => "whatsapp",
+            NotificationChannel::Email => "email",
+            NotificationChannel::Telegram => "telegram",
+        }
+    }
+}
+
+impl Display for NotificationChannel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub enum TrackingEventMsgType {
+    #[serde(rename = "tracking.added")]
+    TrackingAdded,
+    #[serde(rename = "tracking.status_updated")]
+    TrackingStatusUpdated,
+}
+
+impl TrackingEventMsgType {
+    pub const ALL: [TrackingEventMsgType; 2] = [
+        TrackingEventMsgType::TrackingAdded,
+        TrackingEventMsgType::TrackingStatusUpdated,
+    ];
+
+    /// same as the serialized form, used as the envelope's `event_type`
+    pub fn name(&self) -> &'static str {
+        match self {
+            TrackingEventMsgType::TrackingAdded => "tracking.added",
+            TrackingEventMsgType::TrackingStatusUpdated => "tracking.status_updated",
+        }
+    }
+
+    pub fn routing_name(&self) -> &'static str {
+        match self {
+            TrackingEventMsgType::TrackingAdded => "tracking_added",
+            TrackingEventMsgType::TrackingStatusUpdated => "tracking_status_updated",
+        }
+    }
+}
+
+/// published by the tracking service, one per recipient and channel
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrackingEventMsg {
+    pub message_id: Uuid,
+    pub event_type: TrackingEventMsgType,
+    pub channel: NotificationChannel,
+    pub user_id: Uuid,
+    pub recipient: String,
+    pub template_code: String,
+    pub payload: TrackingMsgPayload,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrackingMsgPayload {
+    pub waybill_id: String,
+    pub status: String,
+    pub courier: String,
+}
+
+impl TrackingEventMsg {
+    /// e.g. `notification.tracking_added.whatsapp`
+    pub fn routing_key(&self) -> String {
+        format!(
+            "notification.{}.{}",
+            self.event_type.routing_name(),
+            self.channel.routing_name()
+        )
+    }
+}
+
+impl Event for TrackingEventMsg {
+    const SCHEMA_VERSION: u16 = 1;
+
+    fn event_type(&self) -> &'static str {
+        self.event_type.name()
+    }
+}
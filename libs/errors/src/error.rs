@@ -8,6 +8,15 @@ pub enum HttpError {
     #[error("{0}")]
     NotFound(String),
 
+    #[error("{0}")]
+    Unauthorized(String),
+
+    #[error("{0}")]
+    Forbidden(String),
+
+    #[error("{0}")]
+    ServiceUnavailable(String),
+
     #[error("Internal server error")]
     InternalServerError(#[from] anyhow::Error),
 }
\ No newline at end of file
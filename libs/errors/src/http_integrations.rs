@@ -1,5 +1,5 @@
 use crate::error::HttpError;
-use axum::extract::rejection::JsonRejection;
+use axum::extract::rejection::{JsonRejection, PathRejection, QueryRejection};
 use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
 use axum::Json;
@@ -10,6 +10,9 @@ impl IntoResponse for HttpError {
         let (status, error_message) = match self {
             Self::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg),
             Self::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
+            Self::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, msg),
+            Self::Forbidden(msg) => (StatusCode::FORBIDDEN, msg),
+            Self::ServiceUnavailable(msg) => (StatusCode::SERVICE_UNAVAILABLE, msg),
             Self::InternalServerError(err) => {
                 tracing::error!("Internal Error: {:?}", err);
                 (
@@ -32,3 +35,15 @@ impl From<JsonRejection> for HttpError {
         Self::BadRequest(re.body_text())
     }
 }
+
+impl From<PathRejection> for HttpError {
+    fn from(re: PathRejection) -> Self {
+        Self::BadRequest(re.body_text())
+    }
+}
+
+impl From<QueryRejection> for HttpError {
+    fn from(re: QueryRejection) -> Self {
+        Self::BadRequest(re.body_text())
+    }
+}
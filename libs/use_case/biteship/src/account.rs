@@ -0,0 +1,85 @@
+use provider::dto::Tenant;
+use std::env;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// which biteship environment the keys belong to. sandbox keys only see test orders
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BiteshipEnv {
+    Sandbox,
+    Production,
+}
+
+impl FromStr for BiteshipEnv {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "sandbox" | "test" => Ok(BiteshipEnv::Sandbox),
+            "production" | "prod" => Ok(BiteshipEnv::Production),
+            other => Err(format!("unknown biteship environment {}", other)),
+        }
+    }
+}
+
+impl Display for BiteshipEnv {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BiteshipEnv::Sandbox => write!(f, "sandbox"),
+            BiteshipEnv::Production => write!(f, "production"),
+        }
+    }
+}
+
+/// api keys per tenant for the selected environment
+#[derive(Clone, Debug)]
+pub struct BiteshipAccounts {
+    pub env: BiteshipEnv,
+    pub internal_key: String,
+    pub external_key: String,
+}
+
+impl BiteshipAccounts {
+    /// `BITESHIP_ENV` picks the environment (sandbox by default). internal shipments
+    /// use `BITESHIP_API_KEY_{TEST,PROD}`, external lookups use
+    /// `BITESHIP_API_KEY_{TEST,PROD}_EXTERNAL` and fall back to the internal key
+    pub fn from_env() -> Self {
+        let env = env::var("BITESHIP_ENV")
+            .map(|v| {
+                v.parse::<BiteshipEnv>()
+                    .expect("BITESHIP_ENV must be sandbox or production")
+            })
+            .unwrap_or(BiteshipEnv::Sandbox);
+
+        let prefix = match env {
+            BiteshipEnv::Sandbox => "BITESHIP_API_KEY_TEST",
+            BiteshipEnv::Production => "BITESHIP_API_KEY_PROD",
+        };
+
+        let internal_key = env::var(prefix).unwrap_or_else(|_| panic!("{} must be set", prefix));
+
+        let external_key = env::var(format!("{}_EXTERNAL", prefix))
+            .ok()
+            .filter(|k| !k.is_empty())
+            .unwrap_or_else(|| {
+                tracing::warn!(
+                    "{}_EXTERNAL is not set, external lookups share the internal biteship key",
+                    prefix
+                );
+                internal_key.clone()
+            });
+
+        Self {
+            env,
+            internal_key,
+            external_key,
+        }
+    }
+
+    pub fn key_for(&self, tenant: Tenant) -> &str {
+        match tenant {
+            Tenant::Internal => &self.internal_key,
+            Tenant::External => &self.external_key,
+        }
+    }
+}
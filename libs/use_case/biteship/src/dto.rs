@@ -3,8 +3,11 @@ pub mod errors {
 
     #[derive(Debug, Clone, Deserialize)]
     pub struct BiteshipError {
+        #[serde(default)]
         pub success: bool,
+        /// human readable message
         pub error: String,
+        #[serde(default)]
         pub code: i32,
     }
 }
@@ -54,3 +57,22 @@ pub mod tracking {
         pub updated_at: DateTime<Utc>,
     }
 }
+
+pub mod webhook {
+    use chrono::{DateTime, Utc};
+    use serde::Deserialize;
+
+    /// body of the `order.status` webhook. other events (e.g. `order.price`)
+    /// share the envelope but don't carry a status
+    #[derive(Deserialize, Clone, PartialEq, Eq, Debug)]
+    pub struct BiteshipWebhookPayload {
+        pub event: String,
+        pub order_id: Option<String>,
+        pub courier_tracking_id: Option<String>,
+        pub courier_waybill_id: Option<String>,
+        pub courier_company: Option<String>,
+        pub status: Option<String>,
+        pub note: Option<String>,
+        pub updated_at: Option<DateTime<Utc>>,
+    }
+}
@@ -1,4 +1,6 @@
 use errors::error::HttpError;
+use provider::error::ProviderError;
+use std::time::Duration;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -9,6 +11,18 @@ pub enum TrackingError {
     #[error("courier not supported")]
     UnsupportedCourier,
 
+    #[error("invalid waybill number: {0}")]
+    InvalidWaybill(String),
+
+    #[error("tracking provider rejected our credentials: {0}")]
+    Unauthorized(String),
+
+    #[error("tracking provider rate limit exceeded")]
+    RateLimited { retry_after: Option<Duration> },
+
+    #[error("tracking provider is unavailable, calls are short-circuited")]
+    CircuitOpen,
+
     #[error("external service error: {0}")]
     ExternalService(String),
 
@@ -17,15 +31,45 @@ pub enum TrackingError {
 
     #[error("duplicate tracking number")]
     DuplicateTrackingNumber,
+
+    #[error("shipment is already tracked by this user")]
+    AlreadySubscribed,
+}
+
+impl From<TrackingError> for ProviderError {
+    fn from(err: TrackingError) -> Self {
+        match err {
+            TrackingError::NotFound => ProviderError::NotFound,
+            TrackingError::UnsupportedCourier => ProviderError::UnsupportedCourier,
+            TrackingError::InvalidWaybill(msg) => ProviderError::InvalidWaybill(msg),
+            TrackingError::RateLimited { retry_after } => {
+                ProviderError::RateLimited { retry_after }
+            }
+            TrackingError::CircuitOpen | TrackingError::ExternalService(_) => {
+                ProviderError::Unavailable(err.to_string())
+            }
+            _ => ProviderError::Internal(err.to_string()),
+        }
+    }
 }
 
 impl From<TrackingError> for HttpError {
     fn from(err: TrackingError) -> Self {
         match err {
             TrackingError::DuplicateTrackingNumber => HttpError::BadRequest(err.to_string()),
+            TrackingError::AlreadySubscribed => HttpError::BadRequest(err.to_string()),
             TrackingError::NotFound => HttpError::NotFound(err.to_string()),
             TrackingError::UnsupportedCourier => HttpError::BadRequest(err.to_string()),
+            TrackingError::InvalidWaybill(_) => HttpError::BadRequest(err.to_string()),
+            TrackingError::RateLimited { .. }
+            | TrackingError::CircuitOpen
+            | TrackingError::ExternalService(_) => {
+                HttpError::ServiceUnavailable(
+                    "tracking provider is unavailable, try again later".to_string(),
+                )
+            }
+            // a bad api key is our problem, not the caller's
             _ => HttpError::InternalServerError(err.into()),
         }
     }
-}
\ No newline at end of file
+}
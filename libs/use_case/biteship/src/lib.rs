@@ -1,39 +1,133 @@
+use crate::account::{BiteshipAccounts, BiteshipEnv};
 use crate::dto::errors::BiteshipError;
 use crate::dto::tracking::BiteshipTrackingResponse;
-use errors::error::HttpError;
-use errors::error::HttpError::{BadRequest, NotFound};
+use async_trait::async_trait;
+use crate::error::TrackingError;
+use crate::rate_limit::{RateLimitConfig, RateLimiter};
+use crate::resilience::{CircuitBreaker, CircuitBreakerConfig, RetryPolicy, is_outage};
+use provider::ProviderClient;
+use provider::dto::{Tenant, TrackingCheckpoint, TrackingSnapshot};
+use provider::error::ProviderError;
+use reqwest::StatusCode;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
 use std::env;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
 
+pub mod account;
 pub mod dto;
 pub mod error;
+pub mod mock;
+pub mod rate_limit;
+pub mod resilience;
+pub mod webhook;
+
+/// platform of the biteship rows in status_mappings
+pub const PROVIDER_NAME: &str = "biteship";
+
+/// waybill is unknown to biteship, either never activated or expired
+pub const WAYBILL_NOT_FOUND: i32 = 40003003;
+
+/// couriers biteship can track
+const COURIERS: &[&str] = &[
+    "anteraja", "borzo", "deliveree", "gojek", "grab", "idexpress", "jne", "jnt", "lalamove",
+    "lion", "ninja", "paxel", "pos", "rara", "rpx", "sap", "sentralcargo", "sicepat", "tiki",
+    "wahana",
+];
 
 #[derive(Clone)]
 pub struct BiteshipUseCase {
     client: reqwest::Client,
     base_url: String,
-    api_key: String,
+    accounts: BiteshipAccounts,
+    retry: RetryPolicy,
+    breaker: CircuitBreaker,
+    internal_limiter: Arc<RateLimiter>,
+    external_limiter: Arc<RateLimiter>,
 }
 
 impl BiteshipUseCase {
     pub fn new(client: reqwest::Client) -> Self {
+        Self::with_accounts(client, BiteshipAccounts::from_env())
+    }
+
+    pub fn with_accounts(client: reqwest::Client, accounts: BiteshipAccounts) -> Self {
         let base_url = env::var("BITESHIP_API_URL").expect("BITESHIP_API_URL must be set");
-        let api_key = env::var("BITESHIP_API_KEY_TEST").expect("BITESHIP_API_KEY must be set");
+        let limits = RateLimitConfig::from_env();
+
+        info!("biteship client running in {} mode", accounts.env);
+
         Self {
             client,
             base_url,
-            api_key,
+            internal_limiter: RateLimiter::for_key(&accounts.internal_key, limits.clone()),
+            external_limiter: RateLimiter::for_key(&accounts.external_key, limits),
+            accounts,
+            retry: RetryPolicy::from_env(),
+            breaker: CircuitBreaker::new(CircuitBreakerConfig::from_env()),
         }
     }
 
-    pub fn set_api_key(&mut self, api_key: String) {
-        self.api_key = api_key;
+    pub fn env(&self) -> BiteshipEnv {
+        self.accounts.env
     }
 
+    /// retries outages and rate limits per the retry policy, and fails fast
+    /// with `CircuitOpen` while biteship keeps failing
     pub async fn fetch_public_tracking(
         &self,
         awb: String,
         courier_code: String,
-    ) -> Result<BiteshipTrackingResponse, HttpError> {
+        tenant: Tenant,
+    ) -> Result<BiteshipTrackingResponse, TrackingError> {
+        let limiter = match tenant {
+            Tenant::Internal => &self.internal_limiter,
+            Tenant::External => &self.external_limiter,
+        };
+        let mut attempt = 1;
+
+        loop {
+            self.breaker.acquire()?;
+            limiter.acquire().await;
+
+            let err = match self.request_tracking(&awb, &courier_code, tenant).await {
+                Ok(resp) => {
+                    self.breaker.record_success();
+                    return Ok(resp);
+                }
+                Err(e) => e,
+            };
+
+            if is_outage(&err) {
+                self.breaker.record_failure();
+            } else {
+                self.breaker.record_success();
+            }
+
+            let Some(delay) = self.retry.backoff(attempt, &err) else {
+                return Err(err);
+            };
+
+            warn!(
+                awb,
+                courier_code,
+                attempt,
+                "biteship request failed ({}), retrying in {}ms",
+                err,
+                delay.as_millis()
+            );
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    async fn request_tracking(
+        &self,
+        awb: &str,
+        courier_code: &str,
+        tenant: Tenant,
+    ) -> Result<BiteshipTrackingResponse, TrackingError> {
         let url = format!(
             "{}/v1/trackings/{}/couriers/{}",
             self.base_url, awb, courier_code
@@ -42,10 +136,10 @@ impl BiteshipUseCase {
         let resp = self
             .client
             .get(url)
-            .header("Authorization", self.api_key.clone())
+            .header("Authorization", self.accounts.key_for(tenant))
             .send()
             .await
-            .map_err(|e| HttpError::InternalServerError(e.into()))?;
+            .map_err(|e| TrackingError::ExternalService(e.to_string()))?;
 
         self.handle_response(resp).await
     }
@@ -53,33 +147,245 @@ impl BiteshipUseCase {
     async fn handle_response(
         &self,
         resp: reqwest::Response,
-    ) -> Result<BiteshipTrackingResponse, HttpError> {
-        if (&resp).status().is_server_error() {
-            return Err(HttpError::InternalServerError(anyhow::anyhow!(
-                "server error"
-            )));
+    ) -> Result<BiteshipTrackingResponse, TrackingError> {
+        let status = resp.status();
+        let retry_after = retry_after(resp.headers());
+        let body = resp
+            .bytes()
+            .await
+            .map_err(|e| TrackingError::ExternalService(e.to_string()))?;
+
+        parse_response(status, retry_after, &body)
+    }
+}
+
+/// biteship sends `Retry-After` in seconds on 429
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    headers
+        .get(RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .map(Duration::from_secs)
+}
+
+/// shared by the real client and the mock, so both fail the same way
+pub(crate) fn parse_response(
+    status: StatusCode,
+    retry_after: Option<Duration>,
+    body: &[u8],
+) -> Result<BiteshipTrackingResponse, TrackingError> {
+    if status.is_client_error() || status.is_server_error() {
+        return Err(match serde_json::from_slice::<BiteshipError>(body) {
+            Ok(bs_err) => map_biteship_error(status, retry_after, bs_err),
+            Err(_) => {
+                let body = String::from_utf8_lossy(body);
+                warn!(%status, %body, "biteship returned an unreadable error body");
+                match status {
+                    StatusCode::TOO_MANY_REQUESTS => TrackingError::RateLimited { retry_after },
+                    _ => TrackingError::ExternalService(format!("biteship returned {}", status)),
+                }
+            }
+        });
+    }
+
+    serde_json::from_slice::<BiteshipTrackingResponse>(body).map_err(|e| {
+        warn!(error = %e, "biteship returned an unexpected tracking response");
+        TrackingError::InvalidResponse
+    })
+}
+
+/// biteship error codes start with the http status (40003003 is a 400), so the status
+/// decides the class and the few codes we know refine it. the upstream message is
+/// logged here since most variants don't carry it further
+fn map_biteship_error(
+    status: StatusCode,
+    retry_after: Option<Duration>,
+    bs_err: BiteshipError,
+) -> TrackingError {
+    warn!(
+        %status,
+        code = bs_err.code,
+        message = %bs_err.error,
+        "biteship request failed"
+    );
+
+    match (status, bs_err.code) {
+        (_, WAYBILL_NOT_FOUND) => TrackingError::NotFound,
+        (StatusCode::NOT_FOUND, _) => TrackingError::NotFound,
+        (StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN, _) => {
+            TrackingError::Unauthorized(bs_err.error)
+        }
+        (StatusCode::TOO_MANY_REQUESTS, _) => TrackingError::RateLimited { retry_after },
+        (s, _) if s.is_server_error() => TrackingError::ExternalService(format!(
+            "biteship returned {} ({}): {}",
+            s, bs_err.code, bs_err.error
+        )),
+        // biteship has no dedicated code for an unknown courier. couriers are checked
+        // against `COURIERS` before calling it, so guessing from the message isn't worth
+        // turning unrelated 400s into a permanent UnsupportedCourier
+        (StatusCode::BAD_REQUEST | StatusCode::UNPROCESSABLE_ENTITY, _) => {
+            TrackingError::InvalidWaybill(bs_err.error)
         }
+        (s, _) => TrackingError::ExternalService(format!(
+            "biteship returned {} ({}): {}",
+            s, bs_err.code, bs_err.error
+        )),
+    }
+}
 
-        if (&resp).status().is_client_error() {
-            let bs_err = resp
-                .json::<BiteshipError>()
-                .await
-                .map_err(|e| HttpError::InternalServerError(anyhow::anyhow!(e)))?;
+/// shared by the real client and the mock, so both normalize the same way
+pub(crate) fn normalize(
+    awb: &str,
+    courier_code: &str,
+    resp: BiteshipTrackingResponse,
+) -> TrackingSnapshot {
+    TrackingSnapshot {
+        provider: PROVIDER_NAME.to_string(),
+        waybill_id: awb.to_string(),
+        courier_code: courier_code.to_string(),
+        status: resp.status,
+        history: resp
+            .history
+            .into_iter()
+            .map(|h| TrackingCheckpoint {
+                status: h.status,
+                description: h.note,
+                location: None,
+                occurred_at: h.updated_at,
+            })
+            .collect(),
+    }
+}
+
+#[async_trait]
+impl ProviderClient for BiteshipUseCase {
+    type Response = BiteshipTrackingResponse;
 
-            return Err(self.map_biteship_error(bs_err));
+    const NAME: &'static str = PROVIDER_NAME;
+
+    fn couriers(&self) -> &[&'static str] {
+        COURIERS
+    }
+
+    async fn fetch(
+        &self,
+        awb: &str,
+        courier_code: &str,
+        tenant: Tenant,
+    ) -> Result<BiteshipTrackingResponse, ProviderError> {
+        Ok(self
+            .fetch_public_tracking(awb.to_string(), courier_code.to_string(), tenant)
+            .await?)
+    }
+
+    fn normalize(
+        &self,
+        awb: &str,
+        courier_code: &str,
+        resp: BiteshipTrackingResponse,
+    ) -> TrackingSnapshot {
+        normalize(awb, courier_code, resp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bs_err(code: i32, error: &str) -> BiteshipError {
+        BiteshipError {
+            success: false,
+            error: error.to_string(),
+            code,
         }
+    }
 
-        resp.json::<BiteshipTrackingResponse>()
-            .await
-            .map_err(|e| HttpError::InternalServerError(anyhow::anyhow!(e)))
+    #[test]
+    fn waybill_not_found_code_wins_over_the_status() {
+        let err = map_biteship_error(StatusCode::BAD_REQUEST, None, bs_err(WAYBILL_NOT_FOUND, ""));
+        assert!(matches!(err, TrackingError::NotFound));
+
+        let err = map_biteship_error(StatusCode::NOT_FOUND, None, bs_err(40400001, "not found"));
+        assert!(matches!(err, TrackingError::NotFound));
     }
 
-    fn map_biteship_error(&self, bs_err: BiteshipError) -> HttpError {
-        match bs_err.error.as_str() {
-            "40003003" => {
-                NotFound("Waybill not found. It's either not activated or expired".to_string())
-            }
-            _ => BadRequest(bs_err.error),
+    #[test]
+    fn auth_failures_are_unauthorized() {
+        for status in [StatusCode::UNAUTHORIZED, StatusCode::FORBIDDEN] {
+            let err = map_biteship_error(status, None, bs_err(40100001, "invalid key"));
+            assert!(matches!(err, TrackingError::Unauthorized(msg) if msg == "invalid key"));
         }
     }
+
+    #[test]
+    fn rate_limit_keeps_retry_after() {
+        let retry_after = Some(Duration::from_secs(7));
+
+        let err = map_biteship_error(
+            StatusCode::TOO_MANY_REQUESTS,
+            retry_after,
+            bs_err(42900001, "slow down"),
+        );
+
+        assert!(matches!(err, TrackingError::RateLimited { retry_after: r } if r == retry_after));
+    }
+
+    #[test]
+    fn server_errors_are_external_service_even_mentioning_the_courier() {
+        let err = map_biteship_error(
+            StatusCode::BAD_GATEWAY,
+            None,
+            bs_err(50200001, "courier api is down"),
+        );
+
+        assert!(matches!(err, TrackingError::ExternalService(_)));
+    }
+
+    #[test]
+    fn courier_in_the_message_is_not_unsupported_courier() {
+        let err = map_biteship_error(
+            StatusCode::BAD_REQUEST,
+            None,
+            bs_err(40000001, "courier field is required"),
+        );
+
+        assert!(matches!(err, TrackingError::InvalidWaybill(_)));
+    }
+
+    #[test]
+    fn other_client_errors_are_invalid_waybill() {
+        for status in [StatusCode::BAD_REQUEST, StatusCode::UNPROCESSABLE_ENTITY] {
+            let err = map_biteship_error(status, None, bs_err(40000002, "waybill is invalid"));
+            assert!(
+                matches!(err, TrackingError::InvalidWaybill(msg) if msg == "waybill is invalid")
+            );
+        }
+    }
+
+    #[test]
+    fn unexpected_statuses_are_external_service() {
+        let err = map_biteship_error(StatusCode::CONFLICT, None, bs_err(40900001, "conflict"));
+        assert!(matches!(err, TrackingError::ExternalService(_)));
+    }
+
+    #[test]
+    fn unreadable_error_body_falls_back_to_the_status() {
+        let retry_after = Some(Duration::from_secs(3));
+
+        let err =
+            parse_response(StatusCode::TOO_MANY_REQUESTS, retry_after, b"<html>").unwrap_err();
+        assert!(matches!(err, TrackingError::RateLimited { retry_after: r } if r == retry_after));
+
+        let err = parse_response(StatusCode::SERVICE_UNAVAILABLE, None, b"").unwrap_err();
+        assert!(matches!(err, TrackingError::ExternalService(_)));
+    }
+
+    #[test]
+    fn unexpected_success_body_is_invalid_response() {
+        let err = parse_response(StatusCode::OK, None, b"{}").unwrap_err();
+        assert!(matches!(err, TrackingError::InvalidResponse));
+    }
 }
@@ -0,0 +1,283 @@
+use crate::dto::tracking::BiteshipTrackingResponse;
+use crate::{COURIERS, PROVIDER_NAME, WAYBILL_NOT_FOUND, normalize, parse_response};
+use async_trait::async_trait;
+use provider::ProviderClient;
+use provider::dto::{Tenant, TrackingSnapshot};
+use provider::error::ProviderError;
+use reqwest::StatusCode;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use std::{env, fs};
+
+/// a fixture file, `<fixtures dir>/<courier_code>/<awb>.json`
+#[derive(Deserialize, Debug)]
+pub struct MockFixture {
+    pub steps: Vec<MockStep>,
+}
+
+/// one scripted answer. the last step whose thresholds are reached is served,
+/// which lets a fixture walk a waybill through its statuses over time
+#[derive(Deserialize, Debug)]
+pub struct MockStep {
+    /// seconds since the waybill was first requested
+    #[serde(default)]
+    pub after_secs: u64,
+    /// number of earlier requests for the waybill
+    #[serde(default)]
+    pub after_calls: u64,
+    /// http status biteship would answer with
+    #[serde(default = "default_status")]
+    pub status: u16,
+    /// `Retry-After` seconds to send along, only meaningful with a 429
+    pub retry_after_secs: Option<u64>,
+    /// biteship's response body, either a tracking response or an error
+    pub body: Value,
+}
+
+fn default_status() -> u16 {
+    200
+}
+
+impl MockFixture {
+    /// the step served for a waybill first requested `elapsed` seconds ago with
+    /// `calls` earlier requests
+    fn step(self, elapsed: u64, calls: u64) -> Option<MockStep> {
+        self.steps
+            .into_iter()
+            .rev()
+            .find(|s| s.after_secs <= elapsed && s.after_calls <= calls)
+    }
+}
+
+#[derive(Debug)]
+struct MockProgress {
+    first_seen: Instant,
+    calls: u64,
+}
+
+/// offline stand-in for biteship serving responses from fixture files.
+/// it answers under the biteship name so the regular status mappings apply
+#[derive(Clone)]
+pub struct MockBiteshipProvider {
+    fixtures_dir: PathBuf,
+    progress: Arc<Mutex<HashMap<(String, String), MockProgress>>>,
+}
+
+impl MockBiteshipProvider {
+    pub fn new(fixtures_dir: PathBuf) -> Self {
+        Self {
+            fixtures_dir,
+            progress: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub fn from_env() -> Self {
+        let dir = env::var("BITESHIP_MOCK_FIXTURES_DIR")
+            .unwrap_or_else(|_| "fixtures/biteship".to_string());
+
+        Self::new(PathBuf::from(dir))
+    }
+
+    /// forgets how far every waybill progressed, fixtures start over from their first step
+    pub fn reset(&self) {
+        self.progress
+            .lock()
+            .expect("mock progress lock poisoned")
+            .clear();
+    }
+
+    fn load_fixture(
+        &self,
+        awb: &str,
+        courier_code: &str,
+    ) -> Result<Option<MockFixture>, ProviderError> {
+        let path = self
+            .fixtures_dir
+            .join(courier_code.to_lowercase())
+            .join(format!("{}.json", awb));
+
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let raw = fs::read(&path)
+            .map_err(|e| ProviderError::Internal(format!("failed to read {:?}: {}", path, e)))?;
+
+        let fixture = serde_json::from_slice::<MockFixture>(&raw)
+            .map_err(|e| ProviderError::Internal(format!("invalid fixture {:?}: {}", path, e)))?;
+
+        Ok(Some(fixture))
+    }
+
+    /// bumps the call counter and returns (elapsed secs, earlier calls)
+    fn advance(&self, awb: &str, courier_code: &str) -> (u64, u64) {
+        let mut progress = self.progress.lock().expect("mock progress lock poisoned");
+
+        let entry = progress
+            .entry((awb.to_string(), courier_code.to_lowercase()))
+            .or_insert_with(|| MockProgress {
+                first_seen: Instant::now(),
+                calls: 0,
+            });
+
+        let seen = (entry.first_seen.elapsed().as_secs(), entry.calls);
+        entry.calls += 1;
+
+        seen
+    }
+}
+
+#[async_trait]
+impl ProviderClient for MockBiteshipProvider {
+    type Response = BiteshipTrackingResponse;
+
+    const NAME: &'static str = PROVIDER_NAME;
+
+    fn couriers(&self) -> &[&'static str] {
+        COURIERS
+    }
+
+    async fn fetch(
+        &self,
+        awb: &str,
+        courier_code: &str,
+        _tenant: Tenant,
+    ) -> Result<BiteshipTrackingResponse, ProviderError> {
+        let (elapsed, calls) = self.advance(awb, courier_code);
+
+        let (status, retry_after, body) = match self.load_fixture(awb, courier_code)? {
+            Some(fixture) => {
+                let step = fixture.step(elapsed, calls).ok_or_else(|| {
+                    ProviderError::Internal(format!(
+                        "fixture for {} has no step for the first request",
+                        awb
+                    ))
+                })?;
+
+                (step.status, step.retry_after_secs, step.body)
+            }
+            // the same answer biteship gives for an unknown waybill
+            None => (
+                400,
+                None,
+                json!({
+                    "success": false,
+                    "error": "Waybill not found. It's either not activated or expired",
+                    "code": WAYBILL_NOT_FOUND,
+                }),
+            ),
+        };
+
+        let status = StatusCode::from_u16(status)
+            .map_err(|e| ProviderError::Internal(format!("invalid fixture status: {}", e)))?;
+
+        let body = serde_json::to_vec(&body).map_err(|e| ProviderError::Internal(e.to_string()))?;
+
+        Ok(parse_response(
+            status,
+            retry_after.map(Duration::from_secs),
+            &body,
+        )?)
+    }
+
+    fn normalize(
+        &self,
+        awb: &str,
+        courier_code: &str,
+        resp: BiteshipTrackingResponse,
+    ) -> TrackingSnapshot {
+        normalize(awb, courier_code, resp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use provider::TrackingProvider;
+
+    fn fixture(steps: &[(u64, u64)]) -> MockFixture {
+        MockFixture {
+            steps: steps
+                .iter()
+                .map(|&(after_secs, after_calls)| MockStep {
+                    after_secs,
+                    after_calls,
+                    status: 200,
+                    retry_after_secs: None,
+                    body: json!({ "after_secs": after_secs, "after_calls": after_calls }),
+                })
+                .collect(),
+        }
+    }
+
+    fn selected(steps: &[(u64, u64)], elapsed: u64, calls: u64) -> Option<(u64, u64)> {
+        fixture(steps)
+            .step(elapsed, calls)
+            .map(|s| (s.after_secs, s.after_calls))
+    }
+
+    #[test]
+    fn first_request_gets_the_first_step() {
+        assert_eq!(selected(&[(0, 0), (0, 1), (0, 2)], 0, 0), Some((0, 0)));
+    }
+
+    #[test]
+    fn calls_advance_through_the_steps() {
+        let steps = [(0, 0), (0, 1), (0, 3)];
+
+        assert_eq!(selected(&steps, 0, 1), Some((0, 1)));
+        assert_eq!(selected(&steps, 0, 2), Some((0, 1)));
+        assert_eq!(selected(&steps, 0, 3), Some((0, 3)));
+        // the last step keeps being served
+        assert_eq!(selected(&steps, 0, 50), Some((0, 3)));
+    }
+
+    #[test]
+    fn elapsed_time_advances_through_the_steps() {
+        let steps = [(0, 0), (60, 0), (3600, 0)];
+
+        assert_eq!(selected(&steps, 59, 10), Some((0, 0)));
+        assert_eq!(selected(&steps, 60, 10), Some((60, 0)));
+        assert_eq!(selected(&steps, 7200, 10), Some((3600, 0)));
+    }
+
+    #[test]
+    fn both_thresholds_must_be_reached() {
+        let steps = [(0, 0), (60, 2)];
+
+        assert_eq!(selected(&steps, 120, 1), Some((0, 0)));
+        assert_eq!(selected(&steps, 30, 5), Some((0, 0)));
+        assert_eq!(selected(&steps, 60, 2), Some((60, 2)));
+    }
+
+    #[test]
+    fn no_step_before_the_first_threshold() {
+        assert_eq!(selected(&[(10, 0)], 0, 0), None);
+        assert_eq!(selected(&[], 0, 0), None);
+    }
+
+    #[test]
+    fn advance_counts_calls_per_waybill() {
+        let mock = MockBiteshipProvider::new(PathBuf::from("does-not-exist"));
+
+        assert_eq!(mock.advance("AWB1", "jne").1, 0);
+        assert_eq!(mock.advance("AWB1", "JNE").1, 1);
+        assert_eq!(mock.advance("AWB2", "jne").1, 0);
+
+        mock.reset();
+        assert_eq!(mock.advance("AWB1", "jne").1, 0);
+    }
+
+    #[tokio::test]
+    async fn missing_fixture_is_not_found() {
+        let mock = MockBiteshipProvider::new(PathBuf::from("does-not-exist"));
+
+        let result = mock.fetch_tracking("AWB1", "jne", Tenant::External).await;
+
+        assert!(matches!(result, Err(ProviderError::NotFound)));
+    }
+}
@@ -0,0 +1,173 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::env;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+/// one limiter per api key for the whole process, so every client and task
+/// using the same key draws from the same quota
+static LIMITERS: LazyLock<Mutex<HashMap<String, Arc<RateLimiter>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Clone, Debug)]
+pub struct RateLimitConfig {
+    /// sustained requests per minute allowed by the biteship plan
+    pub requests_per_minute: u32,
+    /// requests that may go out back to back after an idle period
+    pub burst: u32,
+}
+
+impl RateLimitConfig {
+    pub fn from_env() -> Self {
+        let parse = |key: &str, default: u32| {
+            env::var(key)
+                .ok()
+                .and_then(|v| v.parse::<u32>().ok())
+                .unwrap_or(default)
+                .max(1)
+        };
+
+        Self {
+            requests_per_minute: parse("BITESHIP_RATE_LIMIT_PER_MINUTE", 60),
+            burst: parse("BITESHIP_RATE_LIMIT_BURST", 10),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Bucket {
+    /// goes negative when callers reserved tokens that haven't been refilled yet
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct RateLimitStats {
+    /// the last characters of the api key, enough to tell keys apart
+    pub key: String,
+    pub requests_per_minute: u32,
+    pub burst: u32,
+    pub available_tokens: f64,
+    /// requests let through
+    pub acquired: u64,
+    /// requests that had to wait for a token
+    pub throttled: u64,
+    /// total time spent waiting, in milliseconds
+    pub waited_ms: u64,
+}
+
+/// token bucket guarding the outbound quota of a single api key
+#[derive(Debug)]
+pub struct RateLimiter {
+    key: String,
+    config: RateLimitConfig,
+    bucket: Mutex<Bucket>,
+    acquired: AtomicU64,
+    throttled: AtomicU64,
+    waited_ms: AtomicU64,
+}
+
+impl RateLimiter {
+    /// the shared limiter of `api_key`, created with `config` on first use
+    pub fn for_key(api_key: &str, config: RateLimitConfig) -> Arc<RateLimiter> {
+        LIMITERS
+            .lock()
+            .expect("rate limiter registry lock poisoned")
+            .entry(api_key.to_string())
+            .or_insert_with(|| {
+                Arc::new(RateLimiter {
+                    key: mask(api_key),
+                    bucket: Mutex::new(Bucket {
+                        tokens: config.burst as f64,
+                        refilled_at: Instant::now(),
+                    }),
+                    config,
+                    acquired: AtomicU64::new(0),
+                    throttled: AtomicU64::new(0),
+                    waited_ms: AtomicU64::new(0),
+                })
+            })
+            .clone()
+    }
+
+    /// waits until the quota allows one more request. callers reserve their token
+    /// up front, so concurrent waiters queue up instead of racing for refills
+    pub async fn acquire(&self) {
+        let wait = {
+            let mut bucket = self.bucket.lock().expect("rate limiter lock poisoned");
+            self.refill(&mut bucket);
+            bucket.tokens -= 1.0;
+
+            (bucket.tokens < 0.0).then(|| Duration::from_secs_f64(-bucket.tokens / self.per_sec()))
+        };
+
+        self.acquired.fetch_add(1, Ordering::Relaxed);
+
+        if let Some(wait) = wait {
+            self.throttled.fetch_add(1, Ordering::Relaxed);
+            self.waited_ms
+                .fetch_add(wait.as_millis() as u64, Ordering::Relaxed);
+            tracing::debug!(
+                "biteship quota exhausted for key {}, waiting {}ms",
+                self.key,
+                wait.as_millis()
+            );
+
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    pub fn stats(&self) -> RateLimitStats {
+        let available_tokens = {
+            let mut bucket = self.bucket.lock().expect("rate limiter lock poisoned");
+            self.refill(&mut bucket);
+            bucket.tokens.max(0.0)
+        };
+
+        RateLimitStats {
+            key: self.key.clone(),
+            requests_per_minute: self.config.requests_per_minute,
+            burst: self.config.burst,
+            available_tokens,
+            acquired: self.acquired.load(Ordering::Relaxed),
+            throttled: self.throttled.load(Ordering::Relaxed),
+            waited_ms: self.waited_ms.load(Ordering::Relaxed),
+        }
+    }
+
+    fn per_sec(&self) -> f64 {
+        self.config.requests_per_minute as f64 / 60.0
+    }
+
+    fn refill(&self, bucket: &mut Bucket) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(bucket.refilled_at).as_secs_f64();
+
+        bucket.tokens = (bucket.tokens + elapsed * self.per_sec()).min(self.config.burst as f64);
+        bucket.refilled_at = now;
+    }
+}
+
+/// stats of every api key used by this process
+pub fn stats() -> Vec<RateLimitStats> {
+    LIMITERS
+        .lock()
+        .expect("rate limiter registry lock poisoned")
+        .values()
+        .map(|l| l.stats())
+        .collect()
+}
+
+fn mask(api_key: &str) -> String {
+    let tail: String = api_key
+        .chars()
+        .rev()
+        .take(4)
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+        .collect();
+
+    format!("...{}", tail)
+}
@@ -0,0 +1,325 @@
+use crate::error::TrackingError;
+use std::env;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+fn env_or<T: FromStr>(key: &str, default: T) -> T {
+    env::var(key)
+        .ok()
+        .and_then(|v| v.parse::<T>().ok())
+        .unwrap_or(default)
+}
+
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    /// total tries per request, the first one included
+    pub max_attempts: u32,
+    /// backoff before the second try, doubled for every try after it
+    pub base_delay: Duration,
+    /// upper bound of a single backoff, also the longest `Retry-After` we wait for
+    pub max_delay: Duration,
+}
+
+impl RetryPolicy {
+    pub fn from_env() -> Self {
+        Self {
+            max_attempts: env_or("BITESHIP_RETRY_MAX_ATTEMPTS", 3).max(1),
+            base_delay: Duration::from_millis(env_or("BITESHIP_RETRY_BASE_DELAY_MS", 200)),
+            max_delay: Duration::from_millis(env_or("BITESHIP_RETRY_MAX_DELAY_MS", 5_000)),
+        }
+    }
+
+    /// how long to wait before trying again after `attempt` failed, None when the
+    /// error is final or we're out of attempts
+    pub fn backoff(&self, attempt: u32, err: &TrackingError) -> Option<Duration> {
+        if attempt >= self.max_attempts {
+            return None;
+        }
+
+        match err {
+            TrackingError::ExternalService(_) => Some(self.jittered(attempt)),
+            TrackingError::RateLimited {
+                retry_after: Some(wait),
+            } => (*wait <= self.max_delay).then_some(*wait),
+            TrackingError::RateLimited { retry_after: None } => Some(self.jittered(attempt)),
+            _ => None,
+        }
+    }
+
+    /// full jitter, a random wait between zero and the exponential backoff
+    fn jittered(&self, attempt: u32) -> Duration {
+        let exp = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt - 1))
+            .min(self.max_delay);
+
+        Duration::from_millis(rand::random_range(0..=exp.as_millis() as u64))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CircuitBreakerConfig {
+    /// consecutive outages that open the circuit
+    pub failure_threshold: u32,
+    /// how long calls are short-circuited before a trial call is let through
+    pub open_for: Duration,
+}
+
+impl CircuitBreakerConfig {
+    pub fn from_env() -> Self {
+        Self {
+            failure_threshold: env_or("BITESHIP_BREAKER_FAILURE_THRESHOLD", 5).max(1),
+            open_for: Duration::from_secs(env_or("BITESHIP_BREAKER_OPEN_SECS", 30)),
+        }
+    }
+}
+
+#[derive(Debug)]
+enum BreakerState {
+    Closed { failures: u32 },
+    Open { until: Instant },
+    /// a single trial call is in flight. if it never reports back, e.g. its future
+    /// was dropped, another trial is let through once `open_for` passed
+    HalfOpen { since: Instant },
+}
+
+/// stops calling biteship while it's down. clones share the same state
+#[derive(Clone)]
+pub struct CircuitBreaker {
+    config: CircuitBreakerConfig,
+    state: Arc<Mutex<BreakerState>>,
+}
+
+impl CircuitBreaker {
+    pub fn new(config: CircuitBreakerConfig) -> Self {
+        Self {
+            config,
+            state: Arc::new(Mutex::new(BreakerState::Closed { failures: 0 })),
+        }
+    }
+
+    /// asks for permission to make a call
+    pub fn acquire(&self) -> Result<(), TrackingError> {
+        let mut state = self.state.lock().expect("circuit breaker lock poisoned");
+
+        let now = Instant::now();
+
+        match *state {
+            BreakerState::Closed { .. } => Ok(()),
+            BreakerState::Open { until } if now >= until => {
+                *state = BreakerState::HalfOpen { since: now };
+                Ok(())
+            }
+            BreakerState::HalfOpen { since } if now >= since + self.config.open_for => {
+                tracing::warn!("biteship circuit trial call never finished, trying another");
+                *state = BreakerState::HalfOpen { since: now };
+                Ok(())
+            }
+            _ => Err(TrackingError::CircuitOpen),
+        }
+    }
+
+    /// biteship answered, even if with an error of ours like an unknown waybill
+    pub fn record_success(&self) {
+        let mut state = self.state.lock().expect("circuit breaker lock poisoned");
+        if !matches!(*state, BreakerState::Closed { failures: 0 }) {
+            tracing::info!("biteship circuit closed");
+        }
+
+        *state = BreakerState::Closed { failures: 0 };
+    }
+
+    /// biteship was unreachable or answered with a server error
+    pub fn record_failure(&self) {
+        let mut state = self.state.lock().expect("circuit breaker lock poisoned");
+
+        let failures = match *state {
+            BreakerState::Closed { failures } => failures + 1,
+            BreakerState::HalfOpen { .. } => self.config.failure_threshold,
+            // a call that started before the circuit opened
+            BreakerState::Open { .. } => return,
+        };
+
+        if failures >= self.config.failure_threshold {
+            tracing::warn!(
+                "biteship circuit opened for {}s after {} failures",
+                self.config.open_for.as_secs(),
+                failures
+            );
+            *state = BreakerState::Open {
+                until: Instant::now() + self.config.open_for,
+            };
+        } else {
+            *state = BreakerState::Closed { failures };
+        }
+    }
+}
+
+/// errors that mean biteship itself is in trouble, as opposed to a bad request
+pub fn is_outage(err: &TrackingError) -> bool {
+    matches!(
+        err,
+        TrackingError::ExternalService(_) | TrackingError::InvalidResponse
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(1_000),
+        }
+    }
+
+    fn breaker(failure_threshold: u32, open_for: Duration) -> CircuitBreaker {
+        CircuitBreaker::new(CircuitBreakerConfig {
+            failure_threshold,
+            open_for,
+        })
+    }
+
+    fn state(breaker: &CircuitBreaker) -> String {
+        format!("{:?}", *breaker.state.lock().unwrap())
+    }
+
+    #[test]
+    fn backoff_retries_outages_within_the_exponential_bound() {
+        let err = TrackingError::ExternalService("502".to_string());
+
+        for _ in 0..50 {
+            assert!(policy().backoff(1, &err).unwrap() <= Duration::from_millis(100));
+            assert!(policy().backoff(2, &err).unwrap() <= Duration::from_millis(200));
+        }
+    }
+
+    #[test]
+    fn backoff_is_capped_at_max_delay() {
+        let policy = RetryPolicy {
+            max_attempts: 10,
+            ..policy()
+        };
+        let err = TrackingError::ExternalService("502".to_string());
+
+        for _ in 0..50 {
+            assert!(policy.backoff(9, &err).unwrap() <= policy.max_delay);
+        }
+    }
+
+    #[test]
+    fn backoff_stops_after_max_attempts() {
+        let err = TrackingError::ExternalService("502".to_string());
+
+        assert!(policy().backoff(3, &err).is_none());
+        assert!(policy().backoff(4, &err).is_none());
+    }
+
+    #[test]
+    fn backoff_honours_retry_after_up_to_max_delay() {
+        let wait = Duration::from_millis(800);
+        let err = TrackingError::RateLimited {
+            retry_after: Some(wait),
+        };
+        assert_eq!(policy().backoff(1, &err), Some(wait));
+
+        let err = TrackingError::RateLimited {
+            retry_after: Some(Duration::from_secs(60)),
+        };
+        assert_eq!(policy().backoff(1, &err), None);
+
+        let err = TrackingError::RateLimited { retry_after: None };
+        assert!(policy().backoff(1, &err).is_some());
+    }
+
+    #[test]
+    fn backoff_never_retries_final_errors() {
+        for err in [
+            TrackingError::NotFound,
+            TrackingError::UnsupportedCourier,
+            TrackingError::InvalidWaybill("bad".to_string()),
+            TrackingError::Unauthorized("key".to_string()),
+            TrackingError::InvalidResponse,
+            TrackingError::CircuitOpen,
+        ] {
+            assert!(policy().backoff(1, &err).is_none(), "{:?}", err);
+        }
+    }
+
+    #[test]
+    fn breaker_opens_after_the_threshold() {
+        let breaker = breaker(3, Duration::from_secs(60));
+
+        breaker.record_failure();
+        breaker.record_failure();
+        assert!(breaker.acquire().is_ok());
+
+        breaker.record_failure();
+        assert!(matches!(breaker.acquire(), Err(TrackingError::CircuitOpen)));
+    }
+
+    #[test]
+    fn breaker_success_resets_the_failure_count() {
+        let breaker = breaker(2, Duration::from_secs(60));
+
+        breaker.record_failure();
+        breaker.record_success();
+        breaker.record_failure();
+
+        assert!(breaker.acquire().is_ok());
+        assert_eq!(state(&breaker), "Closed { failures: 1 }");
+    }
+
+    #[test]
+    fn breaker_lets_one_trial_through_after_open_for() {
+        let breaker = breaker(1, Duration::ZERO);
+        breaker.record_failure();
+
+        assert!(breaker.acquire().is_ok());
+        assert!(state(&breaker).starts_with("HalfOpen"));
+    }
+
+    #[test]
+    fn breaker_blocks_while_the_trial_is_in_flight() {
+        let breaker = breaker(1, Duration::from_secs(60));
+        *breaker.state.lock().unwrap() = BreakerState::HalfOpen {
+            since: Instant::now(),
+        };
+
+        assert!(matches!(breaker.acquire(), Err(TrackingError::CircuitOpen)));
+    }
+
+    #[test]
+    fn breaker_trial_outcome_closes_or_reopens() {
+        let breaker = breaker(3, Duration::ZERO);
+        for _ in 0..3 {
+            breaker.record_failure();
+        }
+
+        breaker.acquire().unwrap();
+        breaker.record_success();
+        assert_eq!(state(&breaker), "Closed { failures: 0 }");
+
+        for _ in 0..3 {
+            breaker.record_failure();
+        }
+        breaker.acquire().unwrap();
+        // a failed trial reopens right away, no matter the threshold
+        breaker.record_failure();
+        assert!(state(&breaker).starts_with("Open"));
+    }
+
+    #[test]
+    fn breaker_lets_another_trial_through_when_one_never_reports_back() {
+        let breaker = breaker(1, Duration::from_secs(60));
+        *breaker.state.lock().unwrap() = BreakerState::HalfOpen {
+            since: Instant::now() - Duration::from_secs(61),
+        };
+
+        assert!(breaker.acquire().is_ok());
+        assert!(matches!(breaker.acquire(), Err(TrackingError::CircuitOpen)));
+    }
+}
@@ -0,0 +1,47 @@
+use std::env;
+
+/// Biteship signs webhooks with a static header whose name and value are
+/// configured on the dashboard, so verifying is a matter of comparing the two
+#[derive(Clone)]
+pub struct BiteshipWebhookVerifier {
+    header_name: String,
+    secret: String,
+}
+
+impl BiteshipWebhookVerifier {
+    /// None unless both `BITESHIP_WEBHOOK_SIGNATURE_KEY` and
+    /// `BITESHIP_WEBHOOK_SIGNATURE_SECRET` are set, webhooks can't be accepted then
+    pub fn from_env() -> Option<Self> {
+        let header_name = env::var("BITESHIP_WEBHOOK_SIGNATURE_KEY")
+            .ok()
+            .filter(|v| !v.is_empty())?;
+        let secret = env::var("BITESHIP_WEBHOOK_SIGNATURE_SECRET")
+            .ok()
+            .filter(|v| !v.is_empty())?;
+
+        Some(Self {
+            header_name: header_name.to_lowercase(),
+            secret,
+        })
+    }
+
+    pub fn header_name(&self) -> &str {
+        self.header_name.as_str()
+    }
+
+    pub fn verify(&self, signature: Option<&str>) -> bool {
+        match signature {
+            Some(signature) => constant_time_eq(signature.as_bytes(), self.secret.as_bytes()),
+            None => false,
+        }
+    }
+}
+
+// compares without short-circuiting so the secret can't be guessed byte by byte from timings
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+
+    a.iter().zip(b.iter()).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
@@ -0,0 +1,381 @@
+use crate::TrackingProvider;
+use crate::dto::{Tenant, TrackingSnapshot};
+use crate::error::ProviderError;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::env;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::watch;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CacheKey {
+    pub provider: String,
+    /// accounts may see a waybill differently, their snapshots aren't shared
+    pub tenant: Tenant,
+    pub waybill_id: String,
+    pub courier_code: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct CacheConfig {
+    /// how long a fetched snapshot is served without asking the provider, 0 disables the cache
+    pub ttl: Duration,
+    /// where snapshots are kept, `memory` or `postgres`
+    pub backend: String,
+    /// in memory entries kept before the older half is dropped, at least 1
+    pub max_entries: usize,
+}
+
+impl CacheConfig {
+    pub fn from_env() -> Self {
+        Self {
+            ttl: Duration::from_secs(
+                env::var("PROVIDER_CACHE_TTL_SECS")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(60),
+            ),
+            backend: env::var("PROVIDER_CACHE_BACKEND").unwrap_or_else(|_| "memory".to_string()),
+            max_entries: env::var("PROVIDER_CACHE_MAX_ENTRIES")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(10_000)
+                .max(1),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        !self.ttl.is_zero()
+    }
+}
+
+/// storage behind the cache. a failing store is treated as a miss, so
+/// implementations log their errors instead of returning them
+#[async_trait]
+pub trait SnapshotStore: Send + Sync {
+    /// the snapshot stored for the key if it was fetched after `fetched_after`
+    async fn get(&self, key: &CacheKey, fetched_after: DateTime<Utc>) -> Option<TrackingSnapshot>;
+
+    async fn put(&self, key: &CacheKey, snapshot: &TrackingSnapshot, fetched_at: DateTime<Utc>);
+}
+
+/// per process store, lost on restart and not shared between replicas
+pub struct MemorySnapshotStore {
+    entries: Mutex<HashMap<CacheKey, (TrackingSnapshot, DateTime<Utc>)>>,
+    max_entries: usize,
+}
+
+impl MemorySnapshotStore {
+    pub fn new(max_entries: usize) -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            max_entries,
+        }
+    }
+}
+
+#[async_trait]
+impl SnapshotStore for MemorySnapshotStore {
+    async fn get(&self, key: &CacheKey, fetched_after: DateTime<Utc>) -> Option<TrackingSnapshot> {
+        let entries = self.entries.lock().expect("snapshot cache lock poisoned");
+
+        entries
+            .get(key)
+            .filter(|(_, fetched_at)| *fetched_at > fetched_after)
+            .map(|(snapshot, _)| snapshot.clone())
+    }
+
+    async fn put(&self, key: &CacheKey, snapshot: &TrackingSnapshot, fetched_at: DateTime<Utc>) {
+        let mut entries = self.entries.lock().expect("snapshot cache lock poisoned");
+
+        if !entries.is_empty() && entries.len() >= self.max_entries {
+            // full, drop the older half
+            let mut ages: Vec<DateTime<Utc>> = entries.values().map(|(_, at)| *at).collect();
+            ages.sort_unstable();
+            let cutoff = ages[ages.len() / 2];
+            entries.retain(|_, (_, at)| *at > cutoff);
+        }
+
+        entries.insert(key.clone(), (snapshot.clone(), fetched_at));
+    }
+}
+
+/// the outcome of a fetch, shared with every lookup that waited on it
+type SharedResult = Option<Result<TrackingSnapshot, ProviderError>>;
+
+/// serves recent snapshots from a store instead of asking the wrapped provider
+/// again, and lets concurrent lookups of the same waybill share one fetch and
+/// its outcome, so a failed fetch isn't repeated by everyone who waited on it
+pub struct CachedProvider {
+    inner: Arc<dyn TrackingProvider>,
+    store: Arc<dyn SnapshotStore>,
+    ttl: Duration,
+    inflight: Mutex<HashMap<CacheKey, watch::Receiver<SharedResult>>>,
+}
+
+impl CachedProvider {
+    pub fn new(
+        inner: Arc<dyn TrackingProvider>,
+        store: Arc<dyn SnapshotStore>,
+        ttl: Duration,
+    ) -> Self {
+        Self {
+            inner,
+            store,
+            ttl,
+            inflight: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn key(&self, awb: &str, courier_code: &str, tenant: Tenant) -> CacheKey {
+        CacheKey {
+            provider: self.inner.name().to_string(),
+            tenant,
+            waybill_id: awb.to_string(),
+            courier_code: courier_code.to_lowercase(),
+        }
+    }
+
+    fn fresh_after(&self) -> DateTime<Utc> {
+        Utc::now() - chrono::Duration::from_std(self.ttl).unwrap_or(chrono::Duration::zero())
+    }
+
+    /// fetches on behalf of everyone waiting on the key and hands them the result
+    async fn lead(
+        &self,
+        key: &CacheKey,
+        tx: watch::Sender<SharedResult>,
+        awb: &str,
+        courier_code: &str,
+        tenant: Tenant,
+    ) -> Result<TrackingSnapshot, ProviderError> {
+        let _guard = InflightGuard {
+            inflight: &self.inflight,
+            key,
+        };
+
+        // a fetch that finished just before we registered may have stored it
+        let result = match self.store.get(key, self.fresh_after()).await {
+            Some(snapshot) => Ok(snapshot),
+            None => {
+                let fetched = self.inner.fetch_tracking(awb, courier_code, tenant).await;
+                if let Ok(snapshot) = &fetched {
+                    self.store.put(key, snapshot, Utc::now()).await;
+                }
+                fetched
+            }
+        };
+
+        tx.send_replace(Some(result.clone()));
+
+        result
+    }
+}
+
+/// unregisters the leading fetch when it ends, also when its future is dropped
+/// midway. its waiters then see a closed channel and one of them takes over
+struct InflightGuard<'a> {
+    inflight: &'a Mutex<HashMap<CacheKey, watch::Receiver<SharedResult>>>,
+    key: &'a CacheKey,
+}
+
+impl Drop for InflightGuard<'_> {
+    fn drop(&mut self) {
+        self.inflight
+            .lock()
+            .expect("inflight lock poisoned")
+            .remove(self.key);
+    }
+}
+
+#[async_trait]
+impl TrackingProvider for CachedProvider {
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+
+    fn supports(&self, courier_code: &str) -> bool {
+        self.inner.supports(courier_code)
+    }
+
+    async fn fetch_tracking(
+        &self,
+        awb: &str,
+        courier_code: &str,
+        tenant: Tenant,
+    ) -> Result<TrackingSnapshot, ProviderError> {
+        let key = self.key(awb, courier_code, tenant);
+
+        if let Some(snapshot) = self.store.get(&key, self.fresh_after()).await {
+            return Ok(snapshot);
+        }
+
+        loop {
+            let (tx, rx) = watch::channel(None);
+
+            let joined = match self
+                .inflight
+                .lock()
+                .expect("inflight lock poisoned")
+                .entry(key.clone())
+            {
+                Entry::Occupied(e) => Some(e.get().clone()),
+                Entry::Vacant(e) => {
+                    e.insert(rx);
+                    None
+                }
+            };
+
+            let Some(mut rx) = joined else {
+                return self.lead(&key, tx, awb, courier_code, tenant).await;
+            };
+
+            // a closed channel without a result means the leader was dropped, go again
+            if let Ok(shared) = rx.wait_for(Option::is_some).await
+                && let Some(result) = &*shared
+            {
+                return result.clone();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    /// answers every lookup with an error after a short delay, counting the calls
+    struct FailingProvider {
+        calls: AtomicU32,
+    }
+
+    #[async_trait]
+    impl TrackingProvider for FailingProvider {
+        fn name(&self) -> &'static str {
+            "failing"
+        }
+
+        fn supports(&self, _courier_code: &str) -> bool {
+            true
+        }
+
+        async fn fetch_tracking(
+            &self,
+            _awb: &str,
+            _courier_code: &str,
+            _tenant: Tenant,
+        ) -> Result<TrackingSnapshot, ProviderError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            Err(ProviderError::Unavailable("down".to_string()))
+        }
+    }
+
+    fn key(tenant: Tenant) -> CacheKey {
+        CacheKey {
+            provider: "failing".to_string(),
+            tenant,
+            waybill_id: "AWB1".to_string(),
+            courier_code: "jne".to_string(),
+        }
+    }
+
+    fn snapshot() -> TrackingSnapshot {
+        TrackingSnapshot {
+            provider: "failing".to_string(),
+            waybill_id: "AWB1".to_string(),
+            courier_code: "jne".to_string(),
+            status: "delivered".to_string(),
+            history: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn memory_store_without_room_still_stores() {
+        let store = MemorySnapshotStore::new(0);
+        let since = Utc::now() - chrono::Duration::minutes(1);
+
+        store
+            .put(&key(Tenant::Internal), &snapshot(), Utc::now())
+            .await;
+
+        assert!(store.get(&key(Tenant::Internal), since).await.is_some());
+    }
+
+    #[tokio::test]
+    async fn memory_store_keeps_tenants_apart() {
+        let store = MemorySnapshotStore::new(10);
+        let since = Utc::now() - chrono::Duration::minutes(1);
+
+        store
+            .put(&key(Tenant::Internal), &snapshot(), Utc::now())
+            .await;
+
+        assert!(store.get(&key(Tenant::External), since).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn concurrent_lookups_share_a_failed_fetch() {
+        let inner = Arc::new(FailingProvider {
+            calls: AtomicU32::new(0),
+        });
+        let cached = Arc::new(CachedProvider::new(
+            inner.clone(),
+            Arc::new(MemorySnapshotStore::new(10)),
+            Duration::from_secs(60),
+        ));
+
+        let lookups: Vec<_> = (0..5)
+            .map(|_| {
+                let cached = cached.clone();
+                tokio::spawn(
+                    async move { cached.fetch_tracking("AWB1", "jne", Tenant::Internal).await },
+                )
+            })
+            .collect();
+
+        for lookup in lookups {
+            let result = lookup.await.unwrap();
+            assert!(matches!(result, Err(ProviderError::Unavailable(_))));
+        }
+
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 1);
+        assert!(cached.inflight.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn waiters_take_over_when_the_leader_is_dropped() {
+        let inner = Arc::new(FailingProvider {
+            calls: AtomicU32::new(0),
+        });
+        let cached = Arc::new(CachedProvider::new(
+            inner.clone(),
+            Arc::new(MemorySnapshotStore::new(10)),
+            Duration::from_secs(60),
+        ));
+
+        let leader = {
+            let cached = cached.clone();
+            tokio::spawn(
+                async move { cached.fetch_tracking("AWB1", "jne", Tenant::Internal).await },
+            )
+        };
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        let waiter = {
+            let cached = cached.clone();
+            tokio::spawn(
+                async move { cached.fetch_tracking("AWB1", "jne", Tenant::Internal).await },
+            )
+        };
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        leader.abort();
+
+        assert!(waiter.await.unwrap().is_err());
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 2);
+    }
+}
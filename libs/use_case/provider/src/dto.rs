@@ -0,0 +1,40 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// whose provider account a lookup is made with, so our own shipments and
+/// lookups on behalf of others don't share credentials or quota
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Tenant {
+    Internal,
+    External,
+}
+
+impl Tenant {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Tenant::Internal => "INTERNAL",
+            Tenant::External => "EXTERNAL",
+        }
+    }
+}
+
+/// provider independent view of a waybill, every provider normalizes its own
+/// response into this shape. statuses are kept raw, they're mapped through
+/// status_mappings with the provider name as platform
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TrackingSnapshot {
+    pub provider: String,
+    pub waybill_id: String,
+    pub courier_code: String,
+    pub status: String,
+    pub history: Vec<TrackingCheckpoint>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TrackingCheckpoint {
+    pub status: String,
+    pub description: String,
+    pub location: Option<String>,
+    pub occurred_at: DateTime<Utc>,
+}
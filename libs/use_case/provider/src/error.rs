@@ -0,0 +1,55 @@
+use errors::error::HttpError;
+use std::time::Duration;
+use thiserror::Error;
+
+/// what a provider lookup can fail with, independent of the provider behind it
+#[derive(Debug, Clone, Error)]
+pub enum ProviderError {
+    #[error("tracking number not found")]
+    NotFound,
+
+    #[error("courier not supported")]
+    UnsupportedCourier,
+
+    #[error("invalid waybill number: {0}")]
+    InvalidWaybill(String),
+
+    #[error("tracking provider rate limit exceeded")]
+    RateLimited { retry_after: Option<Duration> },
+
+    #[error("tracking provider is unavailable: {0}")]
+    Unavailable(String),
+
+    #[error("tracking provider failed: {0}")]
+    Internal(String),
+}
+
+impl ProviderError {
+    /// asking again won't change the answer, e.g. the waybill expired
+    pub fn is_permanent(&self) -> bool {
+        matches!(
+            self,
+            ProviderError::NotFound
+                | ProviderError::UnsupportedCourier
+                | ProviderError::InvalidWaybill(_)
+        )
+    }
+}
+
+impl From<ProviderError> for HttpError {
+    fn from(err: ProviderError) -> Self {
+        match err {
+            ProviderError::NotFound => HttpError::NotFound(err.to_string()),
+            ProviderError::UnsupportedCourier | ProviderError::InvalidWaybill(_) => {
+                HttpError::BadRequest(err.to_string())
+            }
+            ProviderError::RateLimited { .. } | ProviderError::Unavailable(_) => {
+                HttpError::ServiceUnavailable(
+                    "tracking provider is unavailable, try again later".to_string(),
+                )
+            }
+            // bad credentials or responses are our problem, not the caller's
+            ProviderError::Internal(_) => HttpError::InternalServerError(err.into()),
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use crate::dto::{Tenant, TrackingSnapshot};
+use crate::error::ProviderError;
+use async_trait::async_trait;
+
+pub mod cache;
+pub mod dto;
+pub mod error;
+pub mod registry;
+
+/// what the rest of the service sees of a provider, through the registry
+#[async_trait]
+pub trait TrackingProvider: Send + Sync {
+    /// unique provider name, also used as the `platform` of its status mappings
+    fn name(&self) -> &'static str;
+
+    fn supports(&self, courier_code: &str) -> bool;
+
+    /// fetches the waybill from the provider with the tenant's account and
+    /// normalizes the response
+    async fn fetch_tracking(
+        &self,
+        awb: &str,
+        courier_code: &str,
+        tenant: Tenant,
+    ) -> Result<TrackingSnapshot, ProviderError>;
+}
+
+/// what a new provider implements, it gets `TrackingProvider` for free.
+/// fetching and normalizing are kept apart so every provider answers in the
+/// same `TrackingSnapshot` shape whatever its api returns
+#[async_trait]
+pub trait ProviderClient: Send + Sync {
+    /// the provider's own tracking response
+    type Response: Send;
+
+    /// unique provider name, also used as the `platform` of its status mappings
+    const NAME: &'static str;
+
+    /// lowercase courier codes the provider can track
+    fn couriers(&self) -> &[&'static str];
+
+    async fn fetch(
+        &self,
+        awb: &str,
+        courier_code: &str,
+        tenant: Tenant,
+    ) -> Result<Self::Response, ProviderError>;
+
+    /// turns the provider's response into the provider independent snapshot,
+    /// statuses stay raw and are mapped through status_mappings later
+    fn normalize(&self, awb: &str, courier_code: &str, resp: Self::Response) -> TrackingSnapshot;
+}
+
+#[async_trait]
+impl<P: ProviderClient> TrackingProvider for P {
+    fn name(&self) -> &'static str {
+        P::NAME
+    }
+
+    fn supports(&self, courier_code: &str) -> bool {
+        self.couriers()
+            .iter()
+            .any(|c| c.eq_ignore_ascii_case(courier_code))
+    }
+
+    async fn fetch_tracking(
+        &self,
+        awb: &str,
+        courier_code: &str,
+        tenant: Tenant,
+    ) -> Result<TrackingSnapshot, ProviderError> {
+        let resp = self.fetch(awb, courier_code, tenant).await?;
+
+        Ok(self.normalize(awb, courier_code, resp))
+    }
+}
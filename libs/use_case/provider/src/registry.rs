@@ -0,0 +1,33 @@
+use crate::TrackingProvider;
+use crate::error::ProviderError;
+use std::sync::Arc;
+
+/// picks the provider in charge of a courier. providers are asked in
+/// registration order, so register the preferred ones first
+#[derive(Clone, Default)]
+pub struct ProviderRegistry {
+    providers: Vec<Arc<dyn TrackingProvider>>,
+}
+
+impl ProviderRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(mut self, provider: Arc<dyn TrackingProvider>) -> Self {
+        self.providers.push(provider);
+        self
+    }
+
+    pub fn resolve(&self, courier_code: &str) -> Result<Arc<dyn TrackingProvider>, ProviderError> {
+        self.providers
+            .iter()
+            .find(|p| p.supports(courier_code))
+            .cloned()
+            .ok_or(ProviderError::UnsupportedCourier)
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<dyn TrackingProvider>> {
+        self.providers.iter().find(|p| p.name() == name).cloned()
+    }
+}
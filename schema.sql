@@ -167,3 +167,103 @@ INSERT INTO shipment_subscriptions (id, user_id, shipment_id, subscribed_statuse
 VALUES
     ('850e8400-e29b-41d4-a716-446655440000', '550e8400-e29b-41d4-a716-446655440000', 'e975bb9f-c0b5-4fe5-a20d-e34eba31dafa', '{OUT_FOR_DELIVERY,DELIVERED,FAILED}', 'Electronics Order', '2024-01-16 09:15:00+00', '2024-01-16 09:15:00+00'),
     ('850e8400-e29b-41d4-a716-446655440001', '550e8400-e29b-41d4-a716-446655440001', 'e975bb9f-c0b5-4fe5-a20d-e34eba31dafa', '{DELIVERED,FAILED, RETURNED}', 'Books Order', '2024-01-22 16:15:00+00', '2024-01-22 16:15:00+00');
+
+ALTER TABLE shipment_subscriptions
+    ADD COLUMN notify_on notification_channel[] NOT NULL DEFAULT '{}';
+
+ALTER TABLE tracking_events
+    ADD CONSTRAINT tracking_events_dedup UNIQUE (shipment_id, raw_status, occurred_at);
+
+ALTER TABLE webhook_logs
+    ADD COLUMN attempts        INT NOT NULL DEFAULT 0,
+    ADD COLUMN last_error      TEXT,
+    ADD COLUMN last_attempt_at TIMESTAMPTZ;
+
+CREATE TABLE provider_tracking_cache
+(
+    provider     TEXT        NOT NULL,
+    waybill_id   TEXT        NOT NULL,
+    courier_code TEXT        NOT NULL,
+    snapshot     JSONB       NOT NULL,
+    fetched_at   TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (provider, waybill_id, courier_code)
+);
+
+ALTER TABLE users
+    ADD COLUMN password_hash TEXT;
+
+-- only the sha256 of a refresh token is stored. rotating a token revokes it and
+-- points it at its successor, tokens of one login share a family
+CREATE TABLE refresh_tokens
+(
+    id          UUID PRIMARY KEY     DEFAULT uuid_generate_v4(),
+    user_id     UUID        NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    family_id   UUID        NOT NULL,
+    token_hash  TEXT        NOT NULL UNIQUE,
+    expires_at  TIMESTAMPTZ NOT NULL,
+    revoked_at  TIMESTAMPTZ,
+    replaced_by UUID REFERENCES refresh_tokens (id),
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX refresh_tokens_family_idx ON refresh_tokens (family_id);
+
+ALTER TABLE users
+    ADD COLUMN telegram_chat_id TEXT;
+
+ALTER TABLE user_notification_preferences
+    ADD COLUMN default_statuses shipment_status[] NOT NULL
+        DEFAULT '{IN_TRANSIT,OUT_FOR_DELIVERY,DELIVERED}';
+
+-- quiet hours are local wall clock times in the user's timezone
+ALTER TABLE user_notification_preferences
+    ADD COLUMN quiet_hours_start TIME,
+    ADD COLUMN quiet_hours_end   TIME,
+    ADD COLUMN timezone          TEXT NOT NULL DEFAULT 'UTC';
+
+-- notifications held back by the notification service until the user's quiet hours end
+CREATE TABLE deferred_notifications (
+    id          UUID PRIMARY KEY,
+    message_id  UUID NOT NULL UNIQUE,
+    user_id     UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    routing_key TEXT NOT NULL,
+    payload     JSONB NOT NULL,
+    release_at  TIMESTAMPTZ NOT NULL,
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX idx_deferred_notifications_release_at ON deferred_notifications (release_at);
+
+-- notification events written together with the status change that caused them,
+-- deleted once they are published
+CREATE TABLE notification_outbox
+(
+    id              UUID PRIMARY KEY,
+    routing_key     TEXT        NOT NULL,
+    correlation_id  UUID        NOT NULL,
+    payload         JSONB       NOT NULL,
+    attempts        INT         NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX idx_notification_outbox_next_attempt_at ON notification_outbox (next_attempt_at);
+
+-- admins are promoted by hand, e.g. UPDATE users SET role = 'admin' WHERE email = '...'
+CREATE TYPE user_role AS ENUM ('user', 'admin');
+
+ALTER TABLE users
+    ADD COLUMN role user_role NOT NULL DEFAULT 'user';
+
+-- outbox messages the relay gave up on stay in notification_outbox, list them with
+-- SELECT * FROM notification_outbox WHERE attempts >= <OUTBOX_RELAY_MAX_ATTEMPTS>
+-- and reset attempts to 0 to have them published again
+
+-- cached snapshots are keyed by the account they were fetched with, the old rows
+-- can't be attributed to one and are only a cache
+TRUNCATE provider_tracking_cache;
+
+ALTER TABLE provider_tracking_cache
+    ADD COLUMN tenant TEXT NOT NULL,
+    DROP CONSTRAINT provider_tracking_cache_pkey,
+    ADD PRIMARY KEY (provider, tenant, waybill_id, courier_code);
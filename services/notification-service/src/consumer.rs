@@ -1,15 +1,25 @@
-use crate::domain::TrackingEventMsg;
+use crate::domain::decode_tracking_event;
 use crate::handler::NotificationHandler;
+use crate::ports::PermanentError;
 use config::rabbitmq::create_channel;
-use futures_util::StreamExt;
+use config::rabbitmq::topology::retry_queue;
+use futures_util::{FutureExt, StreamExt};
 use lapin::Channel;
-use lapin::options::{BasicAckOptions, BasicConsumeOptions};
-use lapin::types::FieldTable;
+use lapin::message::Delivery;
+use lapin::options::{BasicAckOptions, BasicConsumeOptions, BasicNackOptions, BasicPublishOptions};
+use lapin::types::{AMQPValue, FieldTable};
+use std::any::Any;
+use std::env;
+use std::panic::AssertUnwindSafe;
+
+static RETRY_COUNT_HEADER: &str = "x-retry-count";
 
 pub struct NotificationConsumer {
     channel: Channel,
     handler: NotificationHandler,
     queue: String,
+    /// deliveries a message gets before it's dead-lettered, the first one included
+    max_attempts: u32,
 }
 
 impl NotificationConsumer {
@@ -20,6 +30,10 @@ impl NotificationConsumer {
             channel,
             handler,
             queue,
+            max_attempts: env::var("NOTIFICATION_MAX_ATTEMPTS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(5),
         }
     }
 
@@ -39,27 +53,161 @@ impl NotificationConsumer {
         while let Some(delivery) = consumer.next().await {
             let delivery = delivery?;
 
-            let event: TrackingEventMsg = serde_json::from_slice(&delivery.data).map_err(|e| {
-                anyhow::anyhow!(
-                    "failed to deserialize event: {}, consumer: {}",
+            if let Err(e) = self.process(&delivery).await {
+                tracing::error!(
+                    "failed to settle delivery {}: {}, consumer: {}",
+                    delivery.delivery_tag,
                     e,
                     self.queue
-                )
-            })?;
-
-            let result = self.handler.handle(&event).await;
-
-            match result {
-                Ok(_) => {
-                    delivery.ack(BasicAckOptions::default()).await?;
-                }
-                Err(e) => {
-                    tracing::error!("failed to handle event: {}, consumer: {}", e, self.queue);
-                    continue;
-                }
+                );
             }
         }
 
         Ok(())
     }
+
+    /// every delivery is acked or nacked here, a message is never left unacked
+    async fn process(&self, delivery: &Delivery) -> anyhow::Result<()> {
+        let event = match decode_tracking_event(&delivery.data) {
+            Ok(event) => event,
+            Err(e) => {
+                // retrying won't fix a message we can't read
+                tracing::error!(
+                    "dead-lettering undecodable message: {}, consumer: {}",
+                    e,
+                    self.queue
+                );
+                return self.dead_letter(delivery).await;
+            }
+        };
+
+        // a panicking sender would otherwise take the whole consumer down with it
+        let handled = AssertUnwindSafe(self.handler.handle(&event))
+            .catch_unwind()
+            .await;
+
+        let result = match handled {
+            Ok(result) => result,
+            Err(panic) => {
+                // a bug, retrying would only panic again
+                tracing::error!(
+                    "handler panicked on message {}, dead-lettering: {}, consumer: {}",
+                    event.data.message_id,
+                    panic_message(&panic),
+                    self.queue
+                );
+                return self.dead_letter(delivery).await;
+            }
+        };
+
+        let Err(e) = result else {
+            delivery.ack(BasicAckOptions::default()).await?;
+            return Ok(());
+        };
+
+        if e.downcast_ref::<PermanentError>().is_some() {
+            tracing::error!(
+                "message {} can't be handled, dead-lettering: {}, consumer: {}",
+                event.data.message_id,
+                e,
+                self.queue
+            );
+            return self.dead_letter(delivery).await;
+        }
+
+        let attempt = retry_count(delivery) + 1;
+
+        if attempt >= self.max_attempts {
+            tracing::error!(
+                "failed to handle message {} after {} attempts, dead-lettering: {}, consumer: {}",
+                event.data.message_id,
+                attempt,
+                e,
+                self.queue
+            );
+            return self.dead_letter(delivery).await;
+        }
+
+        tracing::warn!(
+            "failed to handle message {} (attempt {}/{}), retrying: {}, consumer: {}",
+            event.data.message_id,
+            attempt,
+            self.max_attempts,
+            e,
+            self.queue
+        );
+
+        match self.schedule_retry(delivery, attempt).await {
+            Ok(_) => {
+                delivery.ack(BasicAckOptions::default()).await?;
+            }
+            Err(e) => {
+                // without the retry queue fall back to an immediate redelivery
+                tracing::error!("failed to schedule retry: {}, consumer: {}", e, self.queue);
+                delivery
+                    .nack(BasicNackOptions {
+                        requeue: true,
+                        ..BasicNackOptions::default()
+                    })
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// parks a copy in `<queue>.retry`, it expires back into the queue after the
+    /// retry delay
+    async fn schedule_retry(&self, delivery: &Delivery, attempt: u32) -> anyhow::Result<()> {
+        let mut headers = delivery.properties.headers().clone().unwrap_or_default();
+        headers.insert(
+            RETRY_COUNT_HEADER.into(),
+            AMQPValue::LongLongInt(attempt as i64),
+        );
+
+        self.channel
+            .basic_publish(
+                "",
+                retry_queue(&self.queue).as_str(),
+                BasicPublishOptions::default(),
+                &delivery.data,
+                delivery.properties.clone().with_headers(headers),
+            )
+            .await?
+            .await?;
+
+        Ok(())
+    }
+
+    /// the queue's dead letter exchange routes it to `<queue>.dlq`
+    async fn dead_letter(&self, delivery: &Delivery) -> anyhow::Result<()> {
+        delivery
+            .nack(BasicNackOptions {
+                requeue: false,
+                ..BasicNackOptions::default()
+            })
+            .await?;
+
+        Ok(())
+    }
+}
+
+fn panic_message(panic: &Box<dyn Any + Send>) -> &str {
+    panic
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown panic")
+}
+
+/// how many times the message was already retried
+fn retry_count(delivery: &Delivery) -> u32 {
+    delivery
+        .properties
+        .headers()
+        .as_ref()
+        .and_then(|h| h.inner().get(RETRY_COUNT_HEADER))
+        .and_then(|v| v.as_long_long_int())
+        .map(|n| n.max(0) as u32)
+        .unwrap_or(0)
 }
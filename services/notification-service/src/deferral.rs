@@ -0,0 +1,217 @@
+use crate::domain::{EventEnvelope, TrackingEventMsg};
+use crate::quiet_hours::QuietHours;
+use chrono::{DateTime, NaiveTime, Utc};
+use chrono_tz::Tz;
+use config::rabbitmq::topology::NOTIFICATION_EXCHANGE;
+use lapin::options::BasicPublishOptions;
+use lapin::{BasicProperties, Channel};
+use serde_json::Value;
+use sqlx::{FromRow, Pool, Postgres};
+use std::env;
+use std::time::Duration;
+use uuid::Uuid;
+
+#[derive(FromRow, Debug)]
+struct QuietHoursRow {
+    quiet_hours_start: Option<NaiveTime>,
+    quiet_hours_end: Option<NaiveTime>,
+    timezone: String,
+}
+
+#[derive(FromRow, Debug)]
+struct DeferredRow {
+    id: Uuid,
+    message_id: Uuid,
+    routing_key: String,
+    payload: Value,
+}
+
+/// notifications held back during a user's quiet hours, stored in postgres so a
+/// restart doesn't lose them. the whole envelope is kept so the released message
+/// still carries its original correlation id
+#[derive(Clone)]
+pub struct DeferralRepository {
+    pool: Pool<Postgres>,
+}
+
+impl DeferralRepository {
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+
+    pub async fn quiet_hours_for(&self, user_id: Uuid) -> anyhow::Result<Option<QuietHours>> {
+        let row = sqlx::query_as::<_, QuietHoursRow>(
+            "SELECT quiet_hours_start, quiet_hours_end, timezone
+                FROM user_notification_preferences WHERE user_id = $1",
+        )
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+
+        let (Some(start), Some(end)) = (row.quiet_hours_start, row.quiet_hours_end) else {
+            return Ok(None);
+        };
+
+        let tz = row.timezone.parse::<Tz>().unwrap_or_else(|_| {
+            tracing::warn!(
+                "user {} has unknown timezone {}, using UTC",
+                user_id,
+                row.timezone
+            );
+            Tz::UTC
+        });
+
+        Ok(Some(QuietHours { start, end, tz }))
+    }
+
+    pub async fn defer(
+        &self,
+        envelope: &EventEnvelope<TrackingEventMsg>,
+        release_at: DateTime<Utc>,
+    ) -> anyhow::Result<()> {
+        let event = &envelope.data;
+
+        sqlx::query(
+            "INSERT INTO deferred_notifications
+                (id, message_id, user_id, routing_key, payload, release_at)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                ON CONFLICT (message_id) DO NOTHING",
+        )
+        .bind(Uuid::new_v4())
+        .bind(event.message_id)
+        .bind(event.user_id)
+        .bind(event.routing_key())
+        .bind(serde_json::to_value(envelope)?)
+        .bind(release_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ReleaseConfig {
+    /// how often due notifications are looked for
+    pub tick_secs: u64,
+    /// max notifications released per tick
+    pub batch_size: i64,
+}
+
+impl ReleaseConfig {
+    pub fn from_env() -> Self {
+        Self {
+            tick_secs: env::var("DEFERRED_RELEASE_TICK_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(30),
+            batch_size: env::var("DEFERRED_RELEASE_BATCH_SIZE")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(100),
+        }
+    }
+}
+
+/// puts deferred notifications back on the exchange once their quiet hours ended,
+/// the regular consumers deliver them from there
+pub struct DeferralReleaser {
+    pool: Pool<Postgres>,
+    channel: Channel,
+    config: ReleaseConfig,
+}
+
+impl DeferralReleaser {
+    pub fn new(pool: Pool<Postgres>, channel: Channel, config: ReleaseConfig) -> Self {
+        Self {
+            pool,
+            channel,
+            config,
+        }
+    }
+
+    pub async fn run(&self) {
+        tracing::info!(
+            "deferred notification releaser started, tick every {}s",
+            self.config.tick_secs
+        );
+
+        let mut interval = tokio::time::interval(Duration::from_secs(self.config.tick_secs));
+
+        loop {
+            interval.tick().await;
+
+            let mut released = 0;
+            while released < self.config.batch_size {
+                match self.release_next().await {
+                    Ok(true) => released += 1,
+                    Ok(false) => break,
+                    Err(e) => {
+                        tracing::error!("failed to release deferred notification: {}", e);
+                        break;
+                    }
+                }
+            }
+
+            if released > 0 {
+                tracing::info!("released {} deferred notifications", released);
+            }
+        }
+    }
+
+    /// releases the oldest due notification, false when none is due. each row gets
+    /// its own short transaction and stays locked only across its own publish, so
+    /// replicas don't release it twice and a failure doesn't undo earlier releases
+    async fn release_next(&self) -> anyhow::Result<bool> {
+        let mut tx = self.pool.begin().await?;
+
+        let row = sqlx::query_as::<_, DeferredRow>(
+            "SELECT id, message_id, routing_key, payload FROM deferred_notifications
+                WHERE release_at <= now()
+                ORDER BY release_at, created_at
+                LIMIT 1
+                FOR UPDATE SKIP LOCKED",
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some(row) = row else {
+            return Ok(false);
+        };
+
+        let envelope: EventEnvelope<TrackingEventMsg> = serde_json::from_value(row.payload)?;
+        let payload = serde_json::to_vec(&envelope)?;
+
+        // the same properties the producer published it with
+        let properties = BasicProperties::default()
+            .with_delivery_mode(2)
+            .with_content_type("application/json".into())
+            .with_message_id(row.message_id.to_string().into())
+            .with_correlation_id(envelope.correlation_id.to_string().into())
+            .with_timestamp(envelope.produced_at.timestamp() as u64);
+
+        self.channel
+            .basic_publish(
+                NOTIFICATION_EXCHANGE,
+                row.routing_key.as_str(),
+                BasicPublishOptions::default(),
+                &payload,
+                properties,
+            )
+            .await?
+            .await?;
+
+        sqlx::query("DELETE FROM deferred_notifications WHERE id = $1")
+            .bind(row.id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(true)
+    }
+}
@@ -1,17 +1,38 @@
-use crate::domain::{NotificationChannel, TemplateId, TrackingEventMsg, TrackingEventMsgType};
+use crate::deferral::DeferralRepository;
+use crate::domain::{
+    EventEnvelope, NotificationChannel, TemplateId, TrackingEventMsg, TrackingEventMsgType,
+};
 use crate::ports::ChannelPort;
+use chrono::Utc;
 use std::sync::Arc;
 
 pub struct NotificationHandler {
     sender: Arc<dyn ChannelPort>,
+    deferrals: DeferralRepository,
 }
 
 impl NotificationHandler {
-    pub async fn new(sender: Arc<dyn ChannelPort>) -> Self {
-        Self { sender }
+    pub async fn new(sender: Arc<dyn ChannelPort>, deferrals: DeferralRepository) -> Self {
+        Self { sender, deferrals }
     }
 
-    pub async fn handle(&self, event: &TrackingEventMsg) -> anyhow::Result<()> {
+    pub async fn handle(&self, envelope: &EventEnvelope<TrackingEventMsg>) -> anyhow::Result<()> {
+        let event = &envelope.data;
+
+        if let Some(quiet_hours) = self.deferrals.quiet_hours_for(event.user_id).await?
+            && let Some(release_at) = quiet_hours.release_at(Utc::now())
+        {
+            self.deferrals.defer(envelope, release_at).await?;
+            tracing::info!(
+                "deferred message {} (correlation {}) for user {} until {}",
+                event.message_id,
+                envelope.correlation_id,
+                event.user_id,
+                release_at
+            );
+            return Ok(());
+        }
+
         let template = self.resolve_template(event)?;
 
         let (content, subject) = self.sender.render(template, &event.payload)?;
@@ -24,15 +45,23 @@ impl NotificationHandler {
     fn resolve_template(&self, event: &TrackingEventMsg) -> anyhow::Result<TemplateId> {
         match (&event.event_type, &event.channel) {
             (TrackingEventMsgType::TrackingAdded, NotificationChannel::Whatsapp) => {
-                Ok(TemplateId::TrackingCreatedWa)
+                Ok(TemplateId::CreatedWa)
             }
             (TrackingEventMsgType::TrackingAdded, NotificationChannel::Email) => {
-                Ok(TemplateId::TrackingCreatedEmail)
+                Ok(TemplateId::CreatedEmail)
             }
             (TrackingEventMsgType::TrackingAdded, NotificationChannel::Telegram) => {
-                Ok(TemplateId::TrackingCreatedTele)
+                Ok(TemplateId::CreatedTele)
+            }
+            (TrackingEventMsgType::TrackingStatusUpdated, NotificationChannel::Whatsapp) => {
+                Ok(TemplateId::UpdatedWa)
+            }
+            (TrackingEventMsgType::TrackingStatusUpdated, NotificationChannel::Email) => {
+                Ok(TemplateId::UpdatedEmail)
+            }
+            (TrackingEventMsgType::TrackingStatusUpdated, NotificationChannel::Telegram) => {
+                Ok(TemplateId::UpdatedTele)
             }
-            _ => Err(anyhow::anyhow!("Unsupported channel")),
         }
     }
 }
@@ -1,28 +1,53 @@
 use crate::consumer::NotificationConsumer;
+use crate::deferral::{DeferralReleaser, DeferralRepository, ReleaseConfig};
+use crate::domain::NotificationChannel;
 use crate::handler::NotificationHandler;
 use crate::ports::email::EmailSmtpSender;
 use crate::ports::telegram::TelegramSender;
 use crate::ports::whatsapp::WhatsappSender;
-use std::env;
+use config::rabbitmq::topology::NotificationTopology;
 use std::sync::Arc;
 
 mod consumer;
+mod deferral;
 mod domain;
 mod handler;
 mod ports;
+mod quiet_hours;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     dotenvy::dotenv().ok();
     observability::init("notification-service");
 
-    let wa_queue = env::var("WA_QUEUE").expect("WA_QUEUE env var not set");
-    let tele_queue = env::var("TELE_QUEUE").expect("TELE_QUEUE env var not set");
-    let email_queue = env::var("EMAIL_QUEUE").expect("EMAIL_QUEUE env var not set");
+    let topology = NotificationTopology::from_env();
+    let setup_channel = config::rabbitmq::create_channel()
+        .await
+        .expect("Failed to create channel");
+    topology
+        .declare(&setup_channel)
+        .await
+        .expect("Failed to declare rabbitmq topology");
 
-    let wa_handler = NotificationHandler::new(Arc::new(WhatsappSender::new())).await;
-    let tele_handler = NotificationHandler::new(Arc::new(TelegramSender::new())).await;
-    let email_handler = NotificationHandler::new(Arc::new(EmailSmtpSender::new().await)).await;
+    let wa_queue = topology
+        .queue_for(&NotificationChannel::Whatsapp)
+        .to_string();
+    let tele_queue = topology
+        .queue_for(&NotificationChannel::Telegram)
+        .to_string();
+    let email_queue = topology.queue_for(&NotificationChannel::Email).to_string();
+
+    let pool = config::postgres::get_db_connection()
+        .await
+        .expect("failed to connect to database");
+    let deferrals = DeferralRepository::new(pool.clone());
+
+    let wa_handler =
+        NotificationHandler::new(Arc::new(WhatsappSender::new()), deferrals.clone()).await;
+    let tele_handler =
+        NotificationHandler::new(Arc::new(TelegramSender::new()), deferrals.clone()).await;
+    let email_handler =
+        NotificationHandler::new(Arc::new(EmailSmtpSender::new().await), deferrals).await;
 
     let mut consumers = Vec::<NotificationConsumer>::new();
     consumers.push(NotificationConsumer::new(wa_handler, wa_queue).await);
@@ -30,8 +55,18 @@ async fn main() -> anyhow::Result<()> {
     consumers.push(NotificationConsumer::new(email_handler, email_queue).await);
 
     let mut tasks = Vec::<tokio::task::JoinHandle<()>>::new();
+    let release_channel = config::rabbitmq::create_channel()
+        .await
+        .expect("Failed to create channel");
+    let releaser = DeferralReleaser::new(pool, release_channel, ReleaseConfig::from_env());
+    tasks.push(tokio::spawn(async move { releaser.run().await }));
+
     for consumer in consumers {
-        let task = tokio::spawn(async move { consumer.start().await.unwrap() });
+        let task = tokio::spawn(async move {
+            if let Err(e) = consumer.start().await {
+                tracing::error!("consumer stopped: {}", e);
+            }
+        });
         tasks.push(task);
     }
 
@@ -58,10 +58,14 @@ impl ChannelPort for EmailSmtpSender {
         let mut handlebars = Handlebars::new();
 
         let template = match template_id {
-            TemplateId::TrackingCreatedEmail => (
+            TemplateId::CreatedEmail => (
                 "templates/tracking_added/email.mustache",
                 "Your Shipment Is On Tracking",
             ),
+            TemplateId::UpdatedEmail => (
+                "templates/tracking_status_updated/email.mustache",
+                "Your Shipment Status Has Been Updated",
+            ),
             _ => return Err(anyhow!("invalid template")),
         };
 
@@ -1,9 +1,23 @@
 use crate::domain::{TemplateId, TrackingEventMsg, TrackingMsgPayload};
+use std::fmt;
 
 pub mod email;
 pub mod telegram;
 pub mod whatsapp;
 
+/// a failure retrying won't fix, like a channel with no provider behind it. the
+/// consumer dead-letters the message on the first attempt instead of retrying it
+#[derive(Debug)]
+pub struct PermanentError(pub String);
+
+impl fmt::Display for PermanentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for PermanentError {}
+
 #[async_trait::async_trait]
 pub trait ChannelPort: Send + Sync {
     async fn send(
@@ -1,17 +1,12 @@
 use crate::domain::{TemplateId, TrackingEventMsg, TrackingMsgPayload};
-use crate::ports::ChannelPort;
-use config::reqwest::get_reqwest_pool;
-use reqwest::Client;
+use crate::ports::{ChannelPort, PermanentError};
 
-pub struct WhatsappSender {
-    client: Client,
-}
+/// not wired to a provider yet
+pub struct WhatsappSender;
 
 impl WhatsappSender {
     pub fn new() -> Self {
-        let client = get_reqwest_pool().expect("Failed to create reqwest pool");
-
-        Self { client }
+        Self
     }
 }
 
@@ -19,19 +14,18 @@ impl WhatsappSender {
 impl ChannelPort for WhatsappSender {
     async fn send(
         &self,
-        event: &TrackingEventMsg,
-        content: String,
-        subject: String,
+        _event: &TrackingEventMsg,
+        _content: String,
+        _subject: String,
     ) -> anyhow::Result<()> {
-        tracing::info!("sending tracking event to whatsapp");
-        todo!()
+        Err(PermanentError("whatsapp sender is not implemented".to_string()).into())
     }
 
     fn render(
         &self,
-        template_id: TemplateId,
-        data: &TrackingMsgPayload,
+        _template_id: TemplateId,
+        _data: &TrackingMsgPayload,
     ) -> anyhow::Result<(String, String)> {
-        todo!()
+        Err(PermanentError("whatsapp templates are not implemented".to_string()).into())
     }
 }
@@ -0,0 +1,140 @@
+use chrono::{DateTime, Days, Duration, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+
+/// a user's do-not-disturb window in their local wall clock time. the window may
+/// wrap past midnight, e.g. 22:00 - 07:00
+#[derive(Debug, Clone)]
+pub struct QuietHours {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+    pub tz: Tz,
+}
+
+impl QuietHours {
+    fn contains(&self, local: NaiveTime) -> bool {
+        if self.start <= self.end {
+            local >= self.start && local < self.end
+        } else {
+            local >= self.start || local < self.end
+        }
+    }
+
+    /// when a notification arriving at `now` may be delivered, None if right away
+    pub fn release_at(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let local = now.with_timezone(&self.tz).naive_local();
+
+        if !self.contains(local.time()) {
+            return None;
+        }
+
+        let day = match local.time() < self.end {
+            true => local.date(),
+            false => local.date().checked_add_days(Days::new(1))?,
+        };
+
+        Some(self.to_utc(day.and_time(self.end)))
+    }
+
+    fn to_utc(&self, local: NaiveDateTime) -> DateTime<Utc> {
+        match self.tz.from_local_datetime(&local).earliest() {
+            Some(t) => t.with_timezone(&Utc),
+            // the end falls into a dst gap, the clock skipped past it
+            None => self.to_utc(local + Duration::hours(1)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quiet(start: (u32, u32), end: (u32, u32), tz: Tz) -> QuietHours {
+        QuietHours {
+            start: NaiveTime::from_hms_opt(start.0, start.1, 0).unwrap(),
+            end: NaiveTime::from_hms_opt(end.0, end.1, 0).unwrap(),
+            tz,
+        }
+    }
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn window_within_a_day() {
+        let q = quiet((13, 0), (15, 0), Tz::UTC);
+
+        assert_eq!(q.release_at(utc("2026-05-10T12:59:00Z")), None);
+        assert_eq!(
+            q.release_at(utc("2026-05-10T13:00:00Z")),
+            Some(utc("2026-05-10T15:00:00Z"))
+        );
+        assert_eq!(
+            q.release_at(utc("2026-05-10T14:30:00Z")),
+            Some(utc("2026-05-10T15:00:00Z"))
+        );
+        assert_eq!(q.release_at(utc("2026-05-10T15:00:00Z")), None);
+    }
+
+    #[test]
+    fn window_wrapping_midnight_before_midnight() {
+        // 23:00 in jakarta (utc+7), released at 07:00 the next local day
+        let q = quiet((22, 0), (7, 0), Tz::Asia__Jakarta);
+
+        assert_eq!(
+            q.release_at(utc("2026-05-10T16:00:00Z")),
+            Some(utc("2026-05-11T00:00:00Z"))
+        );
+    }
+
+    #[test]
+    fn window_wrapping_midnight_after_midnight() {
+        // 06:00 in jakarta, released at 07:00 the same local day
+        let q = quiet((22, 0), (7, 0), Tz::Asia__Jakarta);
+
+        assert_eq!(
+            q.release_at(utc("2026-05-10T23:00:00Z")),
+            Some(utc("2026-05-11T00:00:00Z"))
+        );
+    }
+
+    #[test]
+    fn outside_a_wrapping_window() {
+        let q = quiet((22, 0), (7, 0), Tz::Asia__Jakarta);
+
+        // 07:00 and 21:59 local
+        assert_eq!(q.release_at(utc("2026-05-11T00:00:00Z")), None);
+        assert_eq!(q.release_at(utc("2026-05-11T14:59:00Z")), None);
+    }
+
+    #[test]
+    fn empty_window_never_defers() {
+        let q = quiet((8, 0), (8, 0), Tz::UTC);
+
+        assert_eq!(q.release_at(utc("2026-05-10T08:00:00Z")), None);
+    }
+
+    #[test]
+    fn end_inside_a_dst_gap_is_moved_past_it() {
+        // new york skips 02:00 - 03:00 on 2026-03-08, 02:30 doesn't exist that night
+        let q = quiet((22, 0), (2, 30), Tz::America__New_York);
+
+        // 23:00 est on 2026-03-07, released at 03:30 edt
+        assert_eq!(
+            q.release_at(utc("2026-03-08T04:00:00Z")),
+            Some(utc("2026-03-08T07:30:00Z"))
+        );
+    }
+
+    #[test]
+    fn end_inside_a_dst_fold_takes_the_first_one() {
+        // new york repeats 01:00 - 02:00 on 2026-11-01
+        let q = quiet((0, 0), (1, 30), Tz::America__New_York);
+
+        // 00:30 edt, released at the first 01:30 (edt) rather than the second (est)
+        assert_eq!(
+            q.release_at(utc("2026-11-01T04:30:00Z")),
+            Some(utc("2026-11-01T05:30:00Z"))
+        );
+    }
+}
@@ -1,24 +1,52 @@
+use crate::auth::jwt::JwtService;
+use crate::repository::outbox_repo::OutboxRepository;
+use crate::repository::preference_repo::PreferenceRepository;
+use crate::repository::provider_cache_repo::ProviderCacheRepository;
+use crate::repository::refresh_token_repo::RefreshTokenRepository;
 use crate::repository::shipment_repo::ShipmentRepository;
 use crate::repository::shipment_status_mapping_repo::ShipmentStatusMappingRepository;
 use crate::repository::shipment_subscription::ShipmentSubsRepository;
+use crate::repository::tracking_event_repo::TrackingEventRepository;
+use crate::repository::tracking_job_repo::TrackingJobRepository;
+use crate::repository::user_repo::UserRepository;
+use crate::repository::webhook_log_repo::WebhookLogRepository;
 use crate::routes::routes;
+use crate::scheduler::outbox_relay::{OutboxRelay, OutboxRelayConfig};
+use crate::scheduler::tracking_scheduler::{SchedulerConfig, TrackingScheduler};
+use crate::scheduler::webhook_replayer::{WebhookReplayConfig, WebhookReplayer};
+use crate::service::auth_service::AuthService;
+use crate::service::preference_service::PreferenceService;
 use crate::service::tracking_service::TrackingService;
+use crate::service::webhook_service::WebhookService;
 use axum::Router;
 use biteship::BiteshipUseCase;
+use biteship::mock::MockBiteshipProvider;
+use biteship::webhook::BiteshipWebhookVerifier;
 use config::postgres::get_db_connection;
 use config::rabbitmq::create_channel;
+use config::rabbitmq::topology::NotificationTopology;
 use config::reqwest::get_reqwest_pool;
+use provider::TrackingProvider;
+use provider::cache::{CacheConfig, CachedProvider, MemorySnapshotStore, SnapshotStore};
+use provider::registry::ProviderRegistry;
 use std::sync::Arc;
 use tokio::net::TcpListener;
 use tracing::info;
 
 pub struct App {
     state: Arc<AppState>,
+    scheduler: TrackingScheduler,
+    webhook_replayer: WebhookReplayer,
+    outbox_relay: OutboxRelay,
 }
 
 #[derive(Clone)]
 pub struct AppState {
     pub service: TrackingService,
+    pub webhook_service: WebhookService,
+    pub auth_service: AuthService,
+    pub preference_service: PreferenceService,
+    pub jwt: JwtService,
 }
 
 impl App {
@@ -32,22 +60,124 @@ impl App {
         let rabbitmq_channel = create_channel()
             .await
             .expect("couldn't create rabbitmq channel");
+        NotificationTopology::from_env()
+            .declare(&rabbitmq_channel)
+            .await
+            .expect("couldn't declare rabbitmq topology");
 
         let repo = ShipmentRepository::new(db.clone()).await;
         let map_repo = ShipmentStatusMappingRepository::new(db.clone()).await;
         let shipment_subs_repo = ShipmentSubsRepository::new(db.clone()).await;
+        let tracking_job_repo = TrackingJobRepository::new(db.clone()).await;
+        let tracking_event_repo = TrackingEventRepository::new(db.clone()).await;
+        let webhook_log_repo = WebhookLogRepository::new(db.clone()).await;
+        let user_repo = UserRepository::new(db.clone()).await;
+        let preference_repo = PreferenceRepository::new(db.clone()).await;
+        let outbox_repo = OutboxRepository::new(db.clone()).await;
+
+        let mock = std::env::var("TRACKING_PROVIDER").as_deref() == Ok("mock");
+
+        let biteship: Arc<dyn TrackingProvider> = match mock {
+            true => {
+                info!("using the mock tracking provider");
+                Arc::new(MockBiteshipProvider::from_env())
+            }
+            false => Arc::new(BiteshipUseCase::new(pool)),
+        };
+
+        // the mock's fixtures advance per request, a cache would hide their steps
+        let cache_config = CacheConfig::from_env();
+        let biteship: Arc<dyn TrackingProvider> = match cache_config.enabled() && !mock {
+            true => {
+                let store: Arc<dyn SnapshotStore> = match cache_config.backend.as_str() {
+                    "postgres" => Arc::new(ProviderCacheRepository::new(db.clone()).await),
+                    _ => Arc::new(MemorySnapshotStore::new(cache_config.max_entries)),
+                };
+                info!(
+                    "caching provider responses for {}s in {}",
+                    cache_config.ttl.as_secs(),
+                    cache_config.backend
+                );
+                Arc::new(CachedProvider::new(biteship, store, cache_config.ttl))
+            }
+            false => biteship,
+        };
+
+        let providers = ProviderRegistry::new().register(biteship);
+
+        let service = TrackingService::new(
+            repo,
+            shipment_subs_repo,
+            map_repo,
+            tracking_job_repo.clone(),
+            tracking_event_repo,
+            user_repo.clone(),
+            preference_repo.clone(),
+            outbox_repo.clone(),
+            providers,
+            rabbitmq_channel,
+        )
+        .await;
+
+        let scheduler = TrackingScheduler::new(
+            service.clone(),
+            tracking_job_repo,
+            SchedulerConfig::from_env(),
+        )
+        .await;
 
-        let bs_uc = BiteshipUseCase::new(pool);
+        let outbox_relay =
+            OutboxRelay::new(service.clone(), outbox_repo, OutboxRelayConfig::from_env()).await;
 
-        let service =
-            TrackingService::new(repo, shipment_subs_repo, map_repo, bs_uc, rabbitmq_channel).await;
+        let webhook_service = WebhookService::new(
+            service.clone(),
+            webhook_log_repo.clone(),
+            BiteshipWebhookVerifier::from_env(),
+        )
+        .await;
 
-        let state = Arc::new(AppState { service });
+        let webhook_replayer = WebhookReplayer::new(
+            webhook_service.clone(),
+            webhook_log_repo,
+            WebhookReplayConfig::from_env(),
+        )
+        .await;
 
-        Self { state }
+        let jwt = JwtService::from_env();
+
+        let auth_service = AuthService::new(
+            user_repo,
+            RefreshTokenRepository::new(db.clone()).await,
+            jwt.clone(),
+        )
+        .await;
+
+        let state = Arc::new(AppState {
+            service,
+            webhook_service,
+            auth_service,
+            preference_service: PreferenceService::new(preference_repo).await,
+            jwt,
+        });
+
+        Self {
+            state,
+            scheduler,
+            webhook_replayer,
+            outbox_relay,
+        }
     }
 
     pub async fn run(&self) {
+        let scheduler = self.scheduler.clone();
+        tokio::spawn(async move { scheduler.run().await });
+
+        let webhook_replayer = self.webhook_replayer.clone();
+        tokio::spawn(async move { webhook_replayer.run().await });
+
+        let outbox_relay = self.outbox_relay.clone();
+        tokio::spawn(async move { outbox_relay.run().await });
+
         let router = Router::new().merge(routes(self.state.clone()));
 
         let listener = TcpListener::bind("0.0.0.0:3000")
@@ -0,0 +1,24 @@
+use errors::error::HttpError;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum AuthError {
+    #[error("email or phone number is already registered")]
+    UserAlreadyExists,
+
+    #[error("invalid email or password")]
+    InvalidCredentials,
+
+    #[error("invalid or expired refresh token")]
+    InvalidRefreshToken,
+}
+
+impl From<AuthError> for HttpError {
+    fn from(err: AuthError) -> Self {
+        match err {
+            AuthError::UserAlreadyExists => HttpError::BadRequest(err.to_string()),
+            AuthError::InvalidCredentials => HttpError::Unauthorized(err.to_string()),
+            AuthError::InvalidRefreshToken => HttpError::Unauthorized(err.to_string()),
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use crate::models::user::UserRole;
+use chrono::Utc;
+use errors::error::HttpError;
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation, decode, encode};
+use serde::{Deserialize, Serialize};
+use std::{env, fs};
+use uuid::Uuid;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Claims {
+    /// id of the user the token was issued to
+    pub sub: Uuid,
+    pub exp: i64,
+    pub iat: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iss: Option<String>,
+    /// role at the time the token was issued, a changed role applies from the next refresh
+    #[serde(default)]
+    pub role: UserRole,
+}
+
+/// issues and validates access tokens. HS256 uses the shared `JWT_SECRET`, RS256
+/// signs with the pem key at `JWT_PRIVATE_KEY_PATH` and validates with the one at
+/// `JWT_PUBLIC_KEY_PATH`
+#[derive(Clone)]
+pub struct JwtService {
+    algorithm: Algorithm,
+    encoding_key: EncodingKey,
+    decoding_key: DecodingKey,
+    validation: Validation,
+    issuer: Option<String>,
+    /// lifetime of an access token in seconds
+    pub access_ttl_secs: i64,
+}
+
+impl JwtService {
+    pub fn from_env() -> Self {
+        let algorithm = match env::var("JWT_ALGORITHM").as_deref() {
+            Ok("RS256") => Algorithm::RS256,
+            Ok("HS256") | Err(_) => Algorithm::HS256,
+            Ok(other) => panic!("unsupported JWT_ALGORITHM {}, use HS256 or RS256", other),
+        };
+
+        let (encoding_key, decoding_key) = match algorithm {
+            Algorithm::RS256 => {
+                let path =
+                    env::var("JWT_PRIVATE_KEY_PATH").expect("JWT_PRIVATE_KEY_PATH must be set");
+                let pem = fs::read(&path).expect("couldn't read JWT_PRIVATE_KEY_PATH");
+                let encoding_key = EncodingKey::from_rsa_pem(&pem)
+                    .expect("JWT_PRIVATE_KEY_PATH is not an rsa pem key");
+
+                let path =
+                    env::var("JWT_PUBLIC_KEY_PATH").expect("JWT_PUBLIC_KEY_PATH must be set");
+                let pem = fs::read(&path).expect("couldn't read JWT_PUBLIC_KEY_PATH");
+                let decoding_key = DecodingKey::from_rsa_pem(&pem)
+                    .expect("JWT_PUBLIC_KEY_PATH is not an rsa pem key");
+
+                (encoding_key, decoding_key)
+            }
+            _ => {
+                let secret = env::var("JWT_SECRET").expect("JWT_SECRET must be set");
+                (
+                    EncodingKey::from_secret(secret.as_bytes()),
+                    DecodingKey::from_secret(secret.as_bytes()),
+                )
+            }
+        };
+
+        let issuer = env::var("JWT_ISSUER").ok();
+
+        let mut validation = Validation::new(algorithm);
+        validation.leeway = env::var("JWT_LEEWAY_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(30);
+        if let Some(issuer) = &issuer {
+            validation.set_issuer(&[issuer]);
+        }
+
+        Self {
+            algorithm,
+            encoding_key,
+            decoding_key,
+            validation,
+            issuer,
+            access_ttl_secs: env::var("JWT_ACCESS_TTL_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(900),
+        }
+    }
+
+    pub fn issue(&self, user_id: Uuid, role: UserRole) -> Result<String, HttpError> {
+        let now = Utc::now().timestamp();
+        let claims = Claims {
+            sub: user_id,
+            exp: now + self.access_ttl_secs,
+            iat: now,
+            iss: self.issuer.clone(),
+            role,
+        };
+
+        encode(&Header::new(self.algorithm), &claims, &self.encoding_key)
+            .map_err(|e| HttpError::InternalServerError(anyhow::anyhow!(e)))
+    }
+
+    pub fn verify(&self, token: &str) -> Result<Claims, HttpError> {
+        decode::<Claims>(token, &self.decoding_key, &self.validation)
+            .map(|data| data.claims)
+            .map_err(|e| {
+                tracing::debug!("rejected bearer token: {}", e);
+                HttpError::Unauthorized("invalid or expired token".to_string())
+            })
+    }
+}
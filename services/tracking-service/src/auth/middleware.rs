@@ -0,0 +1,70 @@
+use crate::app::AppState;
+use crate::models::user::UserRole;
+use axum::extract::{FromRequestParts, Request, State};
+use axum::http::header::AUTHORIZATION;
+use axum::http::request::Parts;
+use axum::middleware::Next;
+use axum::response::Response;
+use errors::error::HttpError;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// the authenticated caller, put in the request extensions by `require_auth`
+#[derive(Clone, Copy, Debug)]
+pub struct AuthUser {
+    pub id: Uuid,
+    pub role: UserRole,
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for AuthUser {
+    type Rejection = HttpError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<AuthUser>()
+            .copied()
+            .ok_or_else(|| HttpError::Unauthorized("missing bearer token".to_string()))
+    }
+}
+
+/// rejects requests without a valid `Authorization: Bearer <jwt>` header
+pub async fn require_auth(
+    State(state): State<Arc<AppState>>,
+    mut req: Request,
+    next: Next,
+) -> Result<Response, HttpError> {
+    let token = req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .ok_or_else(|| HttpError::Unauthorized("missing bearer token".to_string()))?;
+
+    let claims = state.jwt.verify(token.trim())?;
+
+    req.extensions_mut().insert(AuthUser {
+        id: claims.sub,
+        role: claims.role,
+    });
+
+    Ok(next.run(req).await)
+}
+
+/// only lets admins through, must run after `require_auth`
+pub async fn require_admin(
+    AuthUser { id, role }: AuthUser,
+    req: Request,
+    next: Next,
+) -> Result<Response, HttpError> {
+    if role != UserRole::Admin {
+        tracing::warn!(
+            "user {} tried to reach admin endpoint {}",
+            id,
+            req.uri().path()
+        );
+        return Err(HttpError::Forbidden("admin role required".to_string()));
+    }
+
+    Ok(next.run(req).await)
+}
@@ -0,0 +1,4 @@
+pub mod error;
+pub mod jwt;
+pub mod middleware;
+pub mod password;
@@ -0,0 +1,30 @@
+use argon2::Argon2;
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use errors::error::HttpError;
+
+/// hashes off the async runtime, argon2 is deliberately slow
+pub async fn hash_password(password: String) -> Result<String, HttpError> {
+    tokio::task::spawn_blocking(move || {
+        let salt = SaltString::generate(&mut OsRng);
+        Argon2::default()
+            .hash_password(password.as_bytes(), &salt)
+            .map(|hash| hash.to_string())
+            .map_err(|e| HttpError::InternalServerError(anyhow::anyhow!(e.to_string())))
+    })
+    .await
+    .map_err(|e| HttpError::InternalServerError(anyhow::anyhow!(e)))?
+}
+
+pub async fn verify_password(password: String, hash: String) -> Result<bool, HttpError> {
+    tokio::task::spawn_blocking(move || {
+        let parsed = PasswordHash::new(&hash)
+            .map_err(|e| HttpError::InternalServerError(anyhow::anyhow!(e.to_string())))?;
+
+        Ok(Argon2::default()
+            .verify_password(password.as_bytes(), &parsed)
+            .is_ok())
+    })
+    .await
+    .map_err(|e| HttpError::InternalServerError(anyhow::anyhow!(e)))?
+}
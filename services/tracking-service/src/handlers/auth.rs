@@ -0,0 +1,53 @@
+use crate::app::AppState;
+use crate::models::dto::{LoginRequest, RefreshTokenRequest, RegisterRequest};
+use axum::Json;
+use axum::extract::State;
+use axum::extract::rejection::JsonRejection;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use errors::error::HttpError;
+use std::sync::Arc;
+
+pub async fn register(
+    State(handler): State<Arc<AppState>>,
+    payload: Result<Json<RegisterRequest>, JsonRejection>,
+) -> Result<impl IntoResponse, HttpError> {
+    let Json(data) = payload?;
+
+    let res = handler.auth_service.register(data).await?;
+
+    Ok((StatusCode::CREATED, res))
+}
+
+pub async fn login(
+    State(handler): State<Arc<AppState>>,
+    payload: Result<Json<LoginRequest>, JsonRejection>,
+) -> Result<impl IntoResponse, HttpError> {
+    let Json(data) = payload?;
+
+    let res = handler.auth_service.login(data).await?;
+
+    Ok(res)
+}
+
+pub async fn refresh(
+    State(handler): State<Arc<AppState>>,
+    payload: Result<Json<RefreshTokenRequest>, JsonRejection>,
+) -> Result<impl IntoResponse, HttpError> {
+    let Json(data) = payload?;
+
+    let res = handler.auth_service.refresh(data).await?;
+
+    Ok(res)
+}
+
+pub async fn logout(
+    State(handler): State<Arc<AppState>>,
+    payload: Result<Json<RefreshTokenRequest>, JsonRejection>,
+) -> Result<impl IntoResponse, HttpError> {
+    let Json(data) = payload?;
+
+    let res = handler.auth_service.logout(data).await?;
+
+    Ok(res)
+}
@@ -1 +1,5 @@
-pub mod tracking;
\ No newline at end of file
+pub mod auth;
+pub mod notification;
+pub mod provider;
+pub mod tracking;
+pub mod webhook;
@@ -0,0 +1,33 @@
+use crate::app::AppState;
+use crate::auth::middleware::AuthUser;
+use crate::models::dto::UpdateNotificationPreferenceRequest;
+use axum::Json;
+use axum::extract::State;
+use axum::extract::rejection::JsonRejection;
+use axum::response::IntoResponse;
+use errors::error::HttpError;
+use std::sync::Arc;
+
+pub async fn get_preferences(
+    State(handler): State<Arc<AppState>>,
+    user: AuthUser,
+) -> Result<impl IntoResponse, HttpError> {
+    let res = handler.preference_service.get_preferences(user.id).await?;
+
+    Ok(res)
+}
+
+pub async fn update_preferences(
+    State(handler): State<Arc<AppState>>,
+    user: AuthUser,
+    payload: Result<Json<UpdateNotificationPreferenceRequest>, JsonRejection>,
+) -> Result<impl IntoResponse, HttpError> {
+    let Json(data) = payload?;
+
+    let res = handler
+        .preference_service
+        .update_preferences(user.id, data)
+        .await?;
+
+    Ok(res)
+}
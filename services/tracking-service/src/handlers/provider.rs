@@ -0,0 +1,10 @@
+use crate::models::dto::RateLimitListResponse;
+use axum::response::IntoResponse;
+use biteship::rate_limit;
+use errors::error::HttpError;
+
+pub async fn get_rate_limits() -> Result<impl IntoResponse, HttpError> {
+    Ok(RateLimitListResponse {
+        data: rate_limit::stats(),
+    })
+}
@@ -1,37 +1,82 @@
 use std::sync::Arc;
 use crate::app::AppState;
-use crate::models::dto::AddTrackingRequest;
-use axum::extract::rejection::JsonRejection;
-use axum::extract::State;
-use axum::http::StatusCode;
+use crate::models::dto::{AddTrackingRequest, ShipmentEventsQuery, ShipmentListQuery};
+use crate::auth::middleware::AuthUser;
+use axum::extract::rejection::{JsonRejection, PathRejection, QueryRejection};
+use axum::extract::{Path, Query, State};
 use axum::response::IntoResponse;
 use axum::Json;
 use errors::error::HttpError;
+use uuid::Uuid;
 
 pub async fn create_shipments(
     State(handler): State<Arc<AppState>>,
+    user: AuthUser,
     payload: Result<Json<AddTrackingRequest>, JsonRejection>,
 ) -> Result<impl IntoResponse, HttpError> {
 
     let Json(data) = payload?;
 
-    let res = handler.service.add_track(&data).await?;
+    let res = handler.service.add_track(user.id, &data).await?;
 
     Ok(res)
 }
 
-pub async fn get_shipments() -> impl IntoResponse {
-    (StatusCode::OK, "ok")
+pub async fn get_shipments(
+    State(handler): State<Arc<AppState>>,
+    user: AuthUser,
+    query: Result<Query<ShipmentListQuery>, QueryRejection>,
+) -> Result<impl IntoResponse, HttpError> {
+    let Query(query) = query?;
+
+    let res = handler.service.list_shipments(user.id, &query).await?;
+
+    Ok(res)
 }
 
-pub async fn get_shipment_by_id() -> impl IntoResponse {
-    (StatusCode::OK, "ok")
+pub async fn get_shipment_by_id(
+    State(handler): State<Arc<AppState>>,
+    user: AuthUser,
+    path: Result<Path<Uuid>, PathRejection>,
+) -> Result<impl IntoResponse, HttpError> {
+    let Path(shipment_id) = path?;
+
+    let res = handler
+        .service
+        .get_shipment(user.id, shipment_id)
+        .await?;
+
+    Ok(res)
 }
 
-pub async fn delete_shipment_by_id() -> impl IntoResponse {
-    (StatusCode::OK, "ok")
+pub async fn delete_shipment_by_id(
+    State(handler): State<Arc<AppState>>,
+    user: AuthUser,
+    path: Result<Path<Uuid>, PathRejection>,
+) -> Result<impl IntoResponse, HttpError> {
+    let Path(shipment_id) = path?;
+
+    let res = handler
+        .service
+        .delete_shipment(user.id, shipment_id)
+        .await?;
+
+    Ok(res)
 }
 
-pub async fn get_shipment_events() -> impl IntoResponse {
-    (StatusCode::OK, "ok")
+pub async fn get_shipment_events(
+    State(handler): State<Arc<AppState>>,
+    user: AuthUser,
+    path: Result<Path<Uuid>, PathRejection>,
+    query: Result<Query<ShipmentEventsQuery>, QueryRejection>,
+) -> Result<impl IntoResponse, HttpError> {
+    let Path(shipment_id) = path?;
+    let Query(query) = query?;
+
+    let res = handler
+        .service
+        .get_shipment_events(user.id, shipment_id, &query)
+        .await?;
+
+    Ok(res)
 }
@@ -0,0 +1,83 @@
+use crate::app::AppState;
+use crate::models::dto::{
+    FailedWebhooksQuery, MessageResponse, ReplayWebhooksRequest, WebhookLogListResponse,
+};
+use crate::models::webhook::WebhookReplayFilter;
+use axum::Json;
+use axum::body::Bytes;
+use axum::extract::rejection::{JsonRejection, QueryRejection};
+use axum::extract::{Query, State};
+use axum::http::HeaderMap;
+use axum::response::IntoResponse;
+use errors::error::HttpError;
+use serde_json::Value;
+use std::sync::Arc;
+
+pub async fn biteship_webhook(
+    State(handler): State<Arc<AppState>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<impl IntoResponse, HttpError> {
+    let Some(verifier) = &handler.webhook_service.verifier else {
+        return Err(HttpError::NotFound("webhooks are not enabled".into()));
+    };
+
+    let signature = headers
+        .get(verifier.header_name())
+        .and_then(|v| v.to_str().ok());
+
+    if !verifier.verify(signature) {
+        return Err(HttpError::Unauthorized("invalid webhook signature".into()));
+    }
+
+    // biteship pings the url with an empty body when the webhook is registered
+    if body.is_empty() {
+        return Ok(MessageResponse {
+            message: "ok".into(),
+        });
+    }
+
+    let payload: Value = serde_json::from_slice(&body)
+        .map_err(|e| HttpError::BadRequest(format!("invalid webhook payload: {}", e)))?;
+
+    handler.webhook_service.receive_biteship(payload).await?;
+
+    Ok(MessageResponse {
+        message: "ok".into(),
+    })
+}
+
+pub async fn replay_webhooks(
+    State(handler): State<Arc<AppState>>,
+    payload: Result<Json<ReplayWebhooksRequest>, JsonRejection>,
+) -> Result<impl IntoResponse, HttpError> {
+    let Json(data) = payload?;
+
+    let filter = WebhookReplayFilter {
+        ids: data.ids,
+        from: data.from,
+        to: data.to,
+        include_processed: data.include_processed,
+    };
+
+    let res = handler
+        .webhook_service
+        .replay_filtered(&filter, data.limit.unwrap_or(100).clamp(1, 1000))
+        .await?;
+
+    Ok(res)
+}
+
+pub async fn get_failed_webhooks(
+    State(handler): State<Arc<AppState>>,
+    query: Result<Query<FailedWebhooksQuery>, QueryRejection>,
+) -> Result<impl IntoResponse, HttpError> {
+    let Query(query) = query?;
+
+    let data = handler
+        .webhook_service
+        .find_failed(query.limit.unwrap_or(50).clamp(1, 500))
+        .await?;
+
+    Ok(WebhookLogListResponse { data })
+}
@@ -2,9 +2,11 @@ use crate::app::App;
 use dotenvy::dotenv;
 
 mod app;
+mod auth;
 mod handlers;
 mod repository;
 mod routes;
+mod scheduler;
 mod service;
 mod models;
 
@@ -1,7 +1,17 @@
 use axum::Json;
 use axum::response::{IntoResponse, Response};
 use serde::{Deserialize, Serialize};
+use chrono::{DateTime, NaiveTime, Utc};
+use uuid::Uuid;
+use crate::models::event::{TrackingEvent, TrackingEventSource};
 use crate::models::notification::NotificationChannel;
+use crate::models::preference::NotificationPreference;
+use crate::models::user::User;
+use crate::models::webhook::WebhookLog;
+use biteship::rate_limit::RateLimitStats;
+use crate::models::shipment::{
+    Shipment, ShipmentSource, ShipmentStatus, ShipmentSubscription, SubscribedShipment,
+};
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct AddTrackingRequest {
@@ -9,16 +19,318 @@ pub struct AddTrackingRequest {
     pub courier_code: String,
     pub label: String,
     pub is_internal: bool,
-    pub notify_on: Vec<NotificationChannel>,
+    /// falls back to the user's default channels when omitted
+    pub notify_on: Option<Vec<NotificationChannel>>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct AddTrackingResponse {
     pub message: String,
+    /// channels a tracking.added notification was sent on
+    pub scheduled_channels: Vec<NotificationChannel>,
+    /// requested channels the user has no contact info for
+    pub skipped_channels: Vec<NotificationChannel>,
 }
 
 impl IntoResponse for AddTrackingResponse {
     fn into_response(self) -> Response {
         Json(self).into_response()
     }
-}
\ No newline at end of file
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MessageResponse {
+    pub message: String,
+}
+
+impl IntoResponse for MessageResponse {
+    fn into_response(self) -> Response {
+        Json(self).into_response()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ShipmentDetailResponse {
+    pub shipment: Shipment,
+    pub subscription: ShipmentSubscription,
+    pub latest_event: Option<ShipmentEventResponse>,
+}
+
+impl IntoResponse for ShipmentDetailResponse {
+    fn into_response(self) -> Response {
+        Json(self).into_response()
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ShipmentListQuery {
+    pub status: Option<ShipmentStatus>,
+    pub courier_code: Option<String>,
+    pub source: Option<ShipmentSource>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ShipmentListResponse {
+    pub data: Vec<SubscribedShipment>,
+    pub total: i64,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+impl IntoResponse for ShipmentListResponse {
+    fn into_response(self) -> Response {
+        Json(self).into_response()
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ShipmentEventsQuery {
+    pub limit: Option<i64>,
+    /// id of the last event from the previous page
+    pub cursor: Option<Uuid>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ShipmentEventResponse {
+    pub id: Uuid,
+    pub status: ShipmentStatus,
+    pub raw_status: String,
+    pub description: String,
+    pub location: Option<String>,
+    pub occurred_at: DateTime<Utc>,
+    pub source: TrackingEventSource,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<TrackingEvent> for ShipmentEventResponse {
+    fn from(event: TrackingEvent) -> Self {
+        Self {
+            id: event.id,
+            status: event.normalized_status,
+            raw_status: event.raw_status,
+            description: event.description,
+            location: event.location,
+            occurred_at: event.occurred_at,
+            source: event.source,
+            created_at: event.created_at,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ShipmentEventListResponse {
+    pub data: Vec<ShipmentEventResponse>,
+    pub next_cursor: Option<Uuid>,
+}
+
+impl IntoResponse for ShipmentEventListResponse {
+    fn into_response(self) -> Response {
+        Json(self).into_response()
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ReplayWebhooksRequest {
+    pub ids: Option<Vec<Uuid>>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub include_processed: bool,
+    pub limit: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct WebhookFailure {
+    pub id: Uuid,
+    pub error: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct WebhookReplayReport {
+    pub total: usize,
+    pub processed: usize,
+    pub failed: Vec<WebhookFailure>,
+}
+
+impl IntoResponse for WebhookReplayReport {
+    fn into_response(self) -> Response {
+        Json(self).into_response()
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct FailedWebhooksQuery {
+    pub limit: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct WebhookLogListResponse {
+    pub data: Vec<WebhookLog>,
+}
+
+impl IntoResponse for WebhookLogListResponse {
+    fn into_response(self) -> Response {
+        Json(self).into_response()
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct RateLimitListResponse {
+    pub data: Vec<RateLimitStats>,
+}
+
+impl IntoResponse for RateLimitListResponse {
+    fn into_response(self) -> Response {
+        Json(self).into_response()
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct RegisterRequest {
+    pub name: String,
+    pub email: String,
+    pub phone_number: Option<String>,
+    pub telegram_chat_id: Option<String>,
+    pub password: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct LoginRequest {
+    pub email: String,
+    pub password: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct RefreshTokenRequest {
+    pub refresh_token: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct UserResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub email: Option<String>,
+    pub phone_number: Option<String>,
+    pub telegram_chat_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<User> for UserResponse {
+    fn from(user: User) -> Self {
+        Self {
+            id: user.id,
+            name: user.name,
+            email: user.email,
+            phone_number: user.phone_number,
+            telegram_chat_id: user.telegram_chat_id,
+            created_at: user.created_at,
+        }
+    }
+}
+
+impl IntoResponse for UserResponse {
+    fn into_response(self) -> Response {
+        Json(self).into_response()
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct TokenResponse {
+    pub access_token: String,
+    pub refresh_token: String,
+    pub token_type: String,
+    /// access token lifetime in seconds
+    pub expires_in: i64,
+}
+
+impl IntoResponse for TokenResponse {
+    fn into_response(self) -> Response {
+        Json(self).into_response()
+    }
+}
+
+/// channels are flattened to flags, matching the preferences contract
+#[derive(Serialize, Debug)]
+pub struct NotificationPreferenceResponse {
+    pub whatsapp: bool,
+    pub email: bool,
+    pub telegram: bool,
+    pub notify_on: Vec<ShipmentStatus>,
+    pub quiet_hours: Option<QuietHours>,
+    pub timezone: String,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// local wall clock window, may wrap past midnight (22:00 - 07:00)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct QuietHours {
+    #[serde(with = "hh_mm")]
+    pub start: NaiveTime,
+    #[serde(with = "hh_mm")]
+    pub end: NaiveTime,
+}
+
+mod hh_mm {
+    use chrono::NaiveTime;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(t: &NaiveTime, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(&t.format("%H:%M").to_string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<NaiveTime, D::Error> {
+        let raw = String::deserialize(d)?;
+        NaiveTime::parse_from_str(&raw, "%H:%M").map_err(serde::de::Error::custom)
+    }
+}
+
+/// tells an explicit null apart from a missing field
+fn deserialize_some<'de, T, D>(d: D) -> Result<Option<T>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: serde::Deserializer<'de>,
+{
+    T::deserialize(d).map(Some)
+}
+
+impl From<NotificationPreference> for NotificationPreferenceResponse {
+    fn from(pref: NotificationPreference) -> Self {
+        Self {
+            whatsapp: pref
+                .default_channels
+                .contains(&NotificationChannel::Whatsapp),
+            email: pref.default_channels.contains(&NotificationChannel::Email),
+            telegram: pref
+                .default_channels
+                .contains(&NotificationChannel::Telegram),
+            notify_on: pref.default_statuses,
+            quiet_hours: pref
+                .quiet_hours_start
+                .zip(pref.quiet_hours_end)
+                .map(|(start, end)| QuietHours { start, end }),
+            timezone: pref.timezone,
+            updated_at: pref.updated_at,
+        }
+    }
+}
+
+impl IntoResponse for NotificationPreferenceResponse {
+    fn into_response(self) -> Response {
+        Json(self).into_response()
+    }
+}
+
+/// omitted fields keep their current value
+#[derive(Deserialize, Debug)]
+pub struct UpdateNotificationPreferenceRequest {
+    pub whatsapp: Option<bool>,
+    pub email: Option<bool>,
+    pub telegram: Option<bool>,
+    pub notify_on: Option<Vec<ShipmentStatus>>,
+    /// null turns quiet hours off
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub quiet_hours: Option<Option<QuietHours>>,
+    pub timezone: Option<String>,
+}
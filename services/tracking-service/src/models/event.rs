@@ -1,9 +1,10 @@
 use crate::models::shipment::ShipmentStatus;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, Type};
 use uuid::Uuid;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(FromRow, Debug, Clone, Serialize, Deserialize)]
 pub struct TrackingEvent {
     pub id: Uuid,
     pub shipment_id: Uuid,
@@ -16,7 +17,9 @@ pub struct TrackingEvent {
     pub created_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Type, Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "UPPERCASE")]
+#[sqlx(type_name = "tracking_event_source", rename_all = "UPPERCASE")]
 pub enum TrackingEventSource {
     Polling,
     Webhook,
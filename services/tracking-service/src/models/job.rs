@@ -0,0 +1,14 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+#[derive(FromRow, Debug, Clone, Serialize, Deserialize)]
+pub struct TrackingJob {
+    pub shipment_id: Uuid,
+    pub next_run_at: DateTime<Utc>,
+    pub interval_minutes: i32,
+    pub attempt: i32,
+    pub is_active: bool,
+    pub updated_at: DateTime<Utc>,
+}
@@ -1,4 +1,9 @@
 pub mod shipment;
 pub mod event;
 pub mod dto;
-pub mod notification;
\ No newline at end of file
+pub mod notification;
+pub mod job;
+pub mod webhook;
+pub mod user;
+pub mod preference;
+pub mod outbox;
@@ -0,0 +1,67 @@
+use crate::models::notification::TrackingEventMsg;
+use contracts::envelope::{Event, EventEnvelope};
+use contracts::notification::{LEGACY_SCHEMA_VERSION, encode_tracking_event};
+use serde_json::Value;
+use sqlx::FromRow;
+use std::env;
+use std::sync::LazyLock;
+use uuid::Uuid;
+
+static PRODUCER_NAME: &str = "tracking-service";
+
+/// the version events are published in, `EVENT_SCHEMA_VERSION=0` sends the bare v1
+/// message while consumers that predate the envelope are still around. see
+/// `contracts::notification` for the rollout order
+static EVENT_SCHEMA_VERSION: LazyLock<u16> = LazyLock::new(|| {
+    let current = TrackingEventMsg::SCHEMA_VERSION;
+
+    match env::var("EVENT_SCHEMA_VERSION")
+        .ok()
+        .map(|v| v.parse::<u16>())
+    {
+        None => current,
+        Some(Ok(v)) if v == LEGACY_SCHEMA_VERSION || v == current => v,
+        Some(_) => {
+            tracing::warn!(
+                "unsupported EVENT_SCHEMA_VERSION, publishing version {}",
+                current
+            );
+            current
+        }
+    }
+});
+
+/// a notification event waiting in `notification_outbox` to be published. it's
+/// written in the same transaction as the status change that caused it, so the
+/// event can't get lost when rabbitmq is down
+#[derive(FromRow, Debug, Clone)]
+pub struct OutboxMessage {
+    /// the event's message id
+    pub id: Uuid,
+    pub routing_key: String,
+    pub correlation_id: Uuid,
+    /// relay publishes tried so far
+    pub attempts: i32,
+    /// the serialized event in the configured schema version, published as is
+    pub payload: Value,
+}
+
+impl OutboxMessage {
+    pub fn tracking_event(
+        msg: TrackingEventMsg,
+        correlation_id: Uuid,
+    ) -> Result<Self, serde_json::Error> {
+        let id = msg.message_id;
+        let routing_key = msg.routing_key();
+        let envelope = EventEnvelope::wrap(msg, PRODUCER_NAME, correlation_id);
+        let payload = encode_tracking_event(&envelope, *EVENT_SCHEMA_VERSION)?;
+
+        Ok(Self {
+            id,
+            routing_key,
+            correlation_id,
+            attempts: 0,
+            payload,
+        })
+    }
+}
@@ -0,0 +1,41 @@
+use crate::models::notification::NotificationChannel;
+use crate::models::shipment::ShipmentStatus;
+use chrono::{DateTime, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// what a new subscription falls back to when the request doesn't say, and when
+/// the user doesn't want to be disturbed
+#[derive(FromRow, Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationPreference {
+    pub user_id: Uuid,
+    pub default_channels: Vec<NotificationChannel>,
+    pub default_statuses: Vec<ShipmentStatus>,
+    /// local time notifications start being held back, None when quiet hours are off
+    pub quiet_hours_start: Option<NaiveTime>,
+    /// local time held back notifications are released
+    pub quiet_hours_end: Option<NaiveTime>,
+    /// iana name the quiet hours are read in
+    pub timezone: String,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl NotificationPreference {
+    /// the column defaults, for users who never saved their preferences
+    pub fn defaults(user_id: Uuid) -> Self {
+        Self {
+            user_id,
+            default_channels: vec![NotificationChannel::Whatsapp, NotificationChannel::Email],
+            default_statuses: vec![
+                ShipmentStatus::InTransit,
+                ShipmentStatus::OutForDelivery,
+                ShipmentStatus::Delivered,
+            ],
+            quiet_hours_start: None,
+            quiet_hours_end: None,
+            timezone: "UTC".to_string(),
+            updated_at: Utc::now(),
+        }
+    }
+}
@@ -1,4 +1,6 @@
+use crate::models::notification::NotificationChannel;
 use async_trait::async_trait;
+use provider::dto::Tenant;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::{FromRow, Type};
@@ -15,13 +17,25 @@ pub struct StatusMapping {
 }
 
 #[derive(Type, Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "UPPERCASE")]
 #[sqlx(type_name = "shipment_source", rename_all = "UPPERCASE")]
 pub enum ShipmentSource {
     Internal,
     External,
 }
 
+impl ShipmentSource {
+    /// the provider account lookups for this shipment are made with
+    pub fn tenant(&self) -> Tenant {
+        match self {
+            ShipmentSource::Internal => Tenant::Internal,
+            ShipmentSource::External => Tenant::External,
+        }
+    }
+}
+
 #[derive(Type, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 #[sqlx(type_name = "shipment_status", rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum ShipmentStatus {
     Created,
@@ -35,9 +49,20 @@ pub enum ShipmentStatus {
     Unknown,
 }
 
+impl ShipmentStatus {
+    /// a shipment in one of these statuses won't change anymore,
+    /// so there is no point in polling the provider for it
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            ShipmentStatus::Delivered | ShipmentStatus::Returned | ShipmentStatus::Cancelled
+        )
+    }
+}
+
 impl Display for ShipmentStatus {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", format!("{:?}", self))
+        write!(f, "{:?}", self)
     }
 }
 
@@ -45,6 +70,7 @@ impl Display for ShipmentStatus {
 pub trait ShipmentStatusParse {
     async fn map_external_status(
         &self,
+        platform: &str,
         external_status: &str,
     ) -> Result<ShipmentStatus, Box<dyn Error>>;
 }
@@ -56,6 +82,7 @@ pub struct Shipment {
     pub courier_code: String,
     pub source: ShipmentSource,
     pub order_id: Option<Uuid>,
+    #[sqlx(rename = "external_order_ref")]
     pub external_ref_id: Option<String>,
     pub current_status: ShipmentStatus,
     pub created_at: DateTime<Utc>,
@@ -67,8 +94,45 @@ pub struct ShipmentSubscription {
     pub id: Uuid,
     pub user_id: Uuid,
     pub shipment_id: Uuid,
+    #[sqlx(rename = "subscribed_statuses")]
     pub subscribed_statues: Vec<ShipmentStatus>,
+    pub notify_on: Vec<NotificationChannel>,
     pub label: String,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
+
+/// a shipment as seen by one of its subscribers
+#[derive(FromRow, Debug, Clone, Serialize, Deserialize)]
+pub struct SubscribedShipment {
+    pub id: Uuid,
+    pub subscription_id: Uuid,
+    pub waybill_id: String,
+    pub courier_code: String,
+    pub source: ShipmentSource,
+    pub current_status: ShipmentStatus,
+    pub label: String,
+    pub subscribed_statuses: Vec<ShipmentStatus>,
+    pub notify_on: Vec<NotificationChannel>,
+    pub subscribed_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ShipmentFilter {
+    pub user_id: Uuid,
+    pub status: Option<ShipmentStatus>,
+    pub courier_code: Option<String>,
+    pub source: Option<ShipmentSource>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UnsubscribeOutcome {
+    /// the user wasn't subscribed to the shipment in the first place
+    NotSubscribed,
+    /// the subscription is gone, other users still follow the shipment
+    Unsubscribed,
+    /// the last subscription is gone, so the shipment and its job went with it
+    ShipmentRemoved,
+}
@@ -0,0 +1,51 @@
+use crate::models::notification::NotificationChannel;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, Type};
+use uuid::Uuid;
+
+/// admins are promoted in the database, registration always creates a `User`
+#[derive(Type, Debug, Clone, Copy, Default, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "user_role", rename_all = "lowercase")]
+pub enum UserRole {
+    #[default]
+    User,
+    Admin,
+}
+
+#[derive(FromRow, Debug, Clone, Serialize, Deserialize)]
+pub struct User {
+    pub id: Uuid,
+    pub name: String,
+    pub phone_number: Option<String>,
+    pub email: Option<String>,
+    pub telegram_chat_id: Option<String>,
+    /// argon2 phc string, None for users seeded before passwords existed
+    #[serde(skip_serializing)]
+    pub password_hash: Option<String>,
+    pub role: UserRole,
+    pub created_at: DateTime<Utc>,
+}
+
+impl User {
+    /// where the user is reached on a channel, None when they left no contact for it
+    pub fn contact_for(&self, ch: &NotificationChannel) -> Option<&str> {
+        let contact = match ch {
+            NotificationChannel::Whatsapp => self.phone_number.as_deref(),
+            NotificationChannel::Email => self.email.as_deref(),
+            NotificationChannel::Telegram => self.telegram_chat_id.as_deref(),
+        };
+
+        contact.filter(|c| !c.trim().is_empty())
+    }
+}
+
+#[derive(FromRow, Debug, Clone)]
+pub struct RefreshToken {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub family_id: Uuid,
+    pub expires_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
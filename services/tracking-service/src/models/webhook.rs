@@ -0,0 +1,25 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+#[derive(FromRow, Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookLog {
+    pub id: Uuid,
+    pub payload: Value,
+    pub processed_at: Option<DateTime<Utc>>,
+    pub attempts: i32,
+    pub last_error: Option<String>,
+    pub last_attempt_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// which webhook logs an admin replay should pick up
+#[derive(Debug, Clone)]
+pub struct WebhookReplayFilter {
+    pub ids: Option<Vec<Uuid>>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub include_processed: bool,
+}
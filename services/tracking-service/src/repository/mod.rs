@@ -1,3 +1,11 @@
+pub mod outbox_repo;
+pub mod preference_repo;
+pub mod provider_cache_repo;
+pub mod refresh_token_repo;
 pub mod shipment_repo;
 pub mod shipment_status_mapping_repo;
-pub mod shipment_subscription;
\ No newline at end of file
+pub mod shipment_subscription;
+pub mod tracking_job_repo;
+pub mod tracking_event_repo;
+pub mod webhook_log_repo;
+pub mod user_repo;
@@ -0,0 +1,84 @@
+use crate::models::outbox::OutboxMessage;
+use chrono::{DateTime, Duration, Utc};
+use sqlx::{PgConnection, Pool, Postgres};
+use std::error::Error;
+use uuid::Uuid;
+
+/// how long a new message is left to the request that queued it, which publishes it
+/// right after its commit, before the relay may claim it
+static INLINE_PUBLISH_LEASE_SECS: i64 = 60;
+
+#[derive(Clone)]
+pub struct OutboxRepository {
+    pub pool: Pool<Postgres>,
+}
+
+impl OutboxRepository {
+    pub async fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+
+    /// queues messages inside the caller's transaction, so they are committed together
+    /// with the change that caused them
+    pub async fn insert(
+        conn: &mut PgConnection,
+        messages: &[OutboxMessage],
+    ) -> Result<(), sqlx::Error> {
+        let next_attempt_at = Utc::now() + Duration::seconds(INLINE_PUBLISH_LEASE_SECS);
+
+        for msg in messages {
+            sqlx::query(
+                "INSERT INTO notification_outbox
+                    (id, routing_key, correlation_id, payload, next_attempt_at)
+                    VALUES ($1, $2, $3, $4, $5)",
+            )
+            .bind(msg.id)
+            .bind(&msg.routing_key)
+            .bind(msg.correlation_id)
+            .bind(&msg.payload)
+            .bind(next_attempt_at)
+            .execute(&mut *conn)
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    /// claims up to `limit` pending messages and hides them until `lease_until`, a
+    /// message whose publish fails is picked up again once the lease expires.
+    /// messages tried `max_attempts` times are left parked in the table
+    pub async fn claim_due(
+        &self,
+        limit: i64,
+        lease_until: DateTime<Utc>,
+        max_attempts: i32,
+    ) -> Result<Vec<OutboxMessage>, Box<dyn Error>> {
+        let messages = sqlx::query_as::<_, OutboxMessage>(
+            "UPDATE notification_outbox SET next_attempt_at = $2, attempts = attempts + 1
+                WHERE id IN (
+                    SELECT id FROM notification_outbox
+                    WHERE next_attempt_at <= now() AND attempts < $3
+                    ORDER BY created_at
+                    LIMIT $1
+                    FOR UPDATE SKIP LOCKED
+                )
+                RETURNING id, routing_key, correlation_id, attempts, payload",
+        )
+        .bind(limit)
+        .bind(lease_until)
+        .bind(max_attempts)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(messages)
+    }
+
+    pub async fn delete(&self, id: Uuid) -> Result<(), Box<dyn Error>> {
+        sqlx::query("DELETE FROM notification_outbox WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,64 @@
+use crate::models::preference::NotificationPreference;
+use sqlx::{Pool, Postgres};
+use std::error::Error;
+use uuid::Uuid;
+
+#[derive(Clone)]
+pub struct PreferenceRepository {
+    pub pool: Pool<Postgres>,
+}
+
+impl PreferenceRepository {
+    pub async fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+
+    pub async fn find_by_user(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Option<NotificationPreference>, Box<dyn Error>> {
+        let pref = sqlx::query_as::<_, NotificationPreference>(
+            "SELECT user_id, COALESCE(default_channels, '{}') AS default_channels,
+                    default_statuses, quiet_hours_start, quiet_hours_end, timezone,
+                    COALESCE(updated_at, now()) AS updated_at
+                FROM user_notification_preferences WHERE user_id = $1",
+        )
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(pref)
+    }
+
+    pub async fn upsert(
+        &self,
+        pref: NotificationPreference,
+    ) -> Result<NotificationPreference, Box<dyn Error>> {
+        let pref = sqlx::query_as::<_, NotificationPreference>(
+            "INSERT INTO user_notification_preferences
+                (user_id, default_channels, default_statuses, quiet_hours_start,
+                 quiet_hours_end, timezone, updated_at)
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+                ON CONFLICT (user_id) DO UPDATE
+                SET default_channels = EXCLUDED.default_channels,
+                    default_statuses = EXCLUDED.default_statuses,
+                    quiet_hours_start = EXCLUDED.quiet_hours_start,
+                    quiet_hours_end = EXCLUDED.quiet_hours_end,
+                    timezone = EXCLUDED.timezone,
+                    updated_at = EXCLUDED.updated_at
+                RETURNING user_id, default_channels, default_statuses, quiet_hours_start,
+                          quiet_hours_end, timezone, updated_at",
+        )
+        .bind(pref.user_id)
+        .bind(pref.default_channels)
+        .bind(pref.default_statuses)
+        .bind(pref.quiet_hours_start)
+        .bind(pref.quiet_hours_end)
+        .bind(pref.timezone)
+        .bind(pref.updated_at)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(pref)
+    }
+}
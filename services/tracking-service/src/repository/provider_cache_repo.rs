@@ -0,0 +1,66 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use provider::cache::{CacheKey, SnapshotStore};
+use provider::dto::TrackingSnapshot;
+use sqlx::types::Json;
+use sqlx::{Pool, Postgres};
+
+/// postgres backed snapshot cache, shared by every replica
+#[derive(Clone)]
+pub struct ProviderCacheRepository {
+    pub pool: Pool<Postgres>,
+}
+
+impl ProviderCacheRepository {
+    pub async fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl SnapshotStore for ProviderCacheRepository {
+    async fn get(&self, key: &CacheKey, fetched_after: DateTime<Utc>) -> Option<TrackingSnapshot> {
+        let row = sqlx::query_scalar::<_, Json<TrackingSnapshot>>(
+            "SELECT snapshot FROM provider_tracking_cache
+                WHERE provider = $1 AND waybill_id = $2 AND courier_code = $3
+                  AND tenant = $4 AND fetched_at > $5",
+        )
+        .bind(&key.provider)
+        .bind(&key.waybill_id)
+        .bind(&key.courier_code)
+        .bind(key.tenant.as_str())
+        .bind(fetched_after)
+        .fetch_optional(&self.pool)
+        .await;
+
+        match row {
+            Ok(row) => row.map(|Json(snapshot)| snapshot),
+            Err(e) => {
+                tracing::warn!("failed to read provider cache: {}", e);
+                None
+            }
+        }
+    }
+
+    async fn put(&self, key: &CacheKey, snapshot: &TrackingSnapshot, fetched_at: DateTime<Utc>) {
+        let res = sqlx::query(
+            "INSERT INTO provider_tracking_cache
+                (provider, waybill_id, courier_code, tenant, snapshot, fetched_at)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                ON CONFLICT (provider, tenant, waybill_id, courier_code)
+                DO UPDATE SET snapshot = EXCLUDED.snapshot, fetched_at = EXCLUDED.fetched_at",
+        )
+        .bind(&key.provider)
+        .bind(&key.waybill_id)
+        .bind(&key.courier_code)
+        .bind(key.tenant.as_str())
+        .bind(Json(snapshot))
+        .bind(fetched_at)
+        .execute(&self.pool)
+        .await;
+
+        if let Err(e) = res {
+            tracing::warn!("failed to write provider cache: {}", e);
+        }
+    }
+}
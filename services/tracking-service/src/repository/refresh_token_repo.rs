@@ -0,0 +1,109 @@
+use crate::models::user::RefreshToken;
+use chrono::{DateTime, Utc};
+use sqlx::{Pool, Postgres};
+use std::error::Error;
+use uuid::Uuid;
+
+#[derive(Clone)]
+pub struct RefreshTokenRepository {
+    pub pool: Pool<Postgres>,
+}
+
+impl RefreshTokenRepository {
+    pub async fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+
+    pub async fn save(
+        &self,
+        user_id: Uuid,
+        family_id: Uuid,
+        token_hash: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<RefreshToken, Box<dyn Error>> {
+        let token = sqlx::query_as::<_, RefreshToken>(
+            "INSERT INTO refresh_tokens (id, user_id, family_id, token_hash, expires_at)
+                VALUES ($1, $2, $3, $4, $5)
+                RETURNING id, user_id, family_id, expires_at, revoked_at",
+        )
+        .bind(Uuid::new_v4())
+        .bind(user_id)
+        .bind(family_id)
+        .bind(token_hash)
+        .bind(expires_at)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(token)
+    }
+
+    pub async fn find_by_hash(
+        &self,
+        token_hash: &str,
+    ) -> Result<Option<RefreshToken>, Box<dyn Error>> {
+        let token = sqlx::query_as::<_, RefreshToken>(
+            "SELECT id, user_id, family_id, expires_at, revoked_at
+                FROM refresh_tokens WHERE token_hash = $1",
+        )
+        .bind(token_hash)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(token)
+    }
+
+    /// revokes `old` and stores its successor in one transaction. returns None when
+    /// `old` was revoked in the meantime, i.e. the same token was used twice
+    pub async fn rotate(
+        &self,
+        old: &RefreshToken,
+        token_hash: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<Option<RefreshToken>, Box<dyn Error>> {
+        let mut tx = self.pool.begin().await?;
+
+        let next = sqlx::query_as::<_, RefreshToken>(
+            "INSERT INTO refresh_tokens (id, user_id, family_id, token_hash, expires_at)
+                VALUES ($1, $2, $3, $4, $5)
+                RETURNING id, user_id, family_id, expires_at, revoked_at",
+        )
+        .bind(Uuid::new_v4())
+        .bind(old.user_id)
+        .bind(old.family_id)
+        .bind(token_hash)
+        .bind(expires_at)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let revoked = sqlx::query(
+            "UPDATE refresh_tokens SET revoked_at = now(), replaced_by = $2
+                WHERE id = $1 AND revoked_at IS NULL",
+        )
+        .bind(old.id)
+        .bind(next.id)
+        .execute(&mut *tx)
+        .await?;
+
+        if revoked.rows_affected() == 0 {
+            tx.rollback().await?;
+            return Ok(None);
+        }
+
+        tx.commit().await?;
+
+        Ok(Some(next))
+    }
+
+    /// revokes every token issued from the same login
+    pub async fn revoke_family(&self, family_id: Uuid) -> Result<(), Box<dyn Error>> {
+        sqlx::query(
+            "UPDATE refresh_tokens SET revoked_at = now()
+                WHERE family_id = $1 AND revoked_at IS NULL",
+        )
+        .bind(family_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -1,6 +1,11 @@
-use crate::models::shipment::{Shipment};
+use crate::models::outbox::OutboxMessage;
+use crate::repository::outbox_repo::OutboxRepository;
+use crate::models::shipment::{Shipment, ShipmentFilter, ShipmentStatus, SubscribedShipment};
 use sqlx::{Pool, Postgres};
 use biteship::error::TrackingError;
+use chrono::{DateTime, Utc};
+use std::error::Error;
+use uuid::Uuid;
 
 #[derive(Clone)]
 pub struct ShipmentRepository {
@@ -12,12 +17,18 @@ impl ShipmentRepository {
         Self { pool }
     }
 
-    pub async fn save(&self, shipment: Shipment) -> Result<(), Option<TrackingError>> {
-        let res = sqlx::query(
+    /// inserts the shipment, or returns the already stored one when the same
+    /// (waybill_id, courier_code) is tracked already
+    pub async fn upsert(&self, shipment: Shipment) -> Result<Shipment, Option<TrackingError>> {
+        sqlx::query_as::<_, Shipment>(
             "INSERT INTO  shipments
                 (id, waybill_id, courier_code,
                  source, current_status, order_id,
-                 external_order_ref, created_at, updated_at) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)"
+                 external_order_ref, created_at, updated_at) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                ON CONFLICT (waybill_id, courier_code)
+                    DO UPDATE SET waybill_id = shipments.waybill_id
+                RETURNING id, waybill_id, courier_code, source, order_id,
+                          external_order_ref, current_status, created_at, updated_at"
         )
             .bind(shipment.id)
             .bind(shipment.waybill_id)
@@ -28,16 +39,129 @@ impl ShipmentRepository {
             .bind(shipment.external_ref_id)
             .bind(shipment.created_at)
             .bind(shipment.updated_at)
-        .execute(&self.pool)
+        .fetch_one(&self.pool)
         .await
         .map_err(|er| {
             self.handle_db_err(er)
-        });
+        })
+    }
+
+    pub async fn find_by_waybill(
+        &self,
+        waybill_id: &str,
+        courier_code: &str,
+    ) -> Result<Option<Shipment>, Box<dyn Error>> {
+        let shipment = sqlx::query_as::<_, Shipment>(
+            "SELECT id, waybill_id, courier_code, source, order_id,
+                    external_order_ref, current_status, created_at, updated_at
+                FROM shipments WHERE waybill_id = $1 AND courier_code = $2",
+        )
+        .bind(waybill_id)
+        .bind(courier_code)
+        .fetch_optional(&self.pool)
+        .await?;
 
-        match res {
-            Ok(_) => Ok(()),
-            Err(e) => Err(e),
+        Ok(shipment)
+    }
+
+    pub async fn find_by_id(&self, id: Uuid) -> Result<Option<Shipment>, Box<dyn Error>> {
+        let shipment = sqlx::query_as::<_, Shipment>(
+            "SELECT id, waybill_id, courier_code, source, order_id,
+                    external_order_ref, current_status, created_at, updated_at
+                FROM shipments WHERE id = $1",
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(shipment)
+    }
+
+    /// moves the shipment to `status` only if it is still in `previous`, and queues
+    /// the notifications for the move in the same transaction.
+    /// returns whether the row was updated
+    pub async fn update_status(
+        &self,
+        id: Uuid,
+        previous: &ShipmentStatus,
+        status: ShipmentStatus,
+        updated_at: DateTime<Utc>,
+        outbox: &[OutboxMessage],
+    ) -> Result<bool, Box<dyn Error>> {
+        let mut tx = self.pool.begin().await?;
+
+        let res = sqlx::query(
+            "UPDATE shipments SET current_status = $3, updated_at = $4
+                WHERE id = $1 AND current_status = $2",
+        )
+        .bind(id)
+        .bind(previous.clone())
+        .bind(status)
+        .bind(updated_at)
+        .execute(&mut *tx)
+        .await?;
+
+        if res.rows_affected() == 0 {
+            tx.rollback().await?;
+            return Ok(false);
         }
+
+        OutboxRepository::insert(&mut tx, outbox).await?;
+
+        tx.commit().await?;
+
+        Ok(true)
+    }
+
+    pub async fn find_subscribed(
+        &self,
+        filter: &ShipmentFilter,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<SubscribedShipment>, Box<dyn Error>> {
+        let shipments = sqlx::query_as::<_, SubscribedShipment>(
+            "SELECT s.id, ss.id AS subscription_id, s.waybill_id, s.courier_code,
+                    s.source, s.current_status, ss.label, ss.subscribed_statuses,
+                    ss.notify_on, ss.created_at AS subscribed_at, s.created_at, s.updated_at
+                FROM shipments s
+                JOIN shipment_subscriptions ss ON ss.shipment_id = s.id
+                WHERE ss.user_id = $1
+                  AND ($2::shipment_status IS NULL OR s.current_status = $2)
+                  AND ($3::text IS NULL OR s.courier_code = $3)
+                  AND ($4::shipment_source IS NULL OR s.source = $4)
+                ORDER BY ss.created_at DESC, s.id
+                LIMIT $5 OFFSET $6",
+        )
+        .bind(filter.user_id)
+        .bind(filter.status.clone())
+        .bind(filter.courier_code.clone())
+        .bind(filter.source.clone())
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(shipments)
+    }
+
+    pub async fn count_subscribed(&self, filter: &ShipmentFilter) -> Result<i64, Box<dyn Error>> {
+        let total: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*)
+                FROM shipments s
+                JOIN shipment_subscriptions ss ON ss.shipment_id = s.id
+                WHERE ss.user_id = $1
+                  AND ($2::shipment_status IS NULL OR s.current_status = $2)
+                  AND ($3::text IS NULL OR s.courier_code = $3)
+                  AND ($4::shipment_source IS NULL OR s.source = $4)",
+        )
+        .bind(filter.user_id)
+        .bind(filter.status.clone())
+        .bind(filter.courier_code.clone())
+        .bind(filter.source.clone())
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(total)
     }
 
     fn handle_db_err(&self, e: sqlx::Error) -> Option<TrackingError> {
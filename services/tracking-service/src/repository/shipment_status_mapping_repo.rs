@@ -18,12 +18,14 @@ impl ShipmentStatusMappingRepository {
 impl ShipmentStatusParse for ShipmentStatusMappingRepository {
     async fn map_external_status(
         &self,
+        platform: &str,
         external_status: &str,
     ) -> Result<ShipmentStatus, Box<dyn Error>> {
         let status: Option<StatusMapping> = query_as(
             "SELECT id, platform, raw_status, normalized_status
-                FROM status_mappings WHERE raw_status = $1",
+                FROM status_mappings WHERE platform = $1 AND raw_status = $2",
         )
+        .bind(platform)
         .bind(external_status)
         .fetch_optional(&self.pool)
         .await?;
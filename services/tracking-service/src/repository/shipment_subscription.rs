@@ -1,6 +1,10 @@
-use crate::models::shipment::ShipmentSubscription;
+use crate::models::outbox::OutboxMessage;
+use crate::models::shipment::{ShipmentSubscription, UnsubscribeOutcome};
+use crate::repository::outbox_repo::OutboxRepository;
+use biteship::error::TrackingError;
 use sqlx::{Pool, Postgres};
 use std::error::Error;
+use uuid::Uuid;
 
 #[derive(Clone)]
 pub struct ShipmentSubsRepository {
@@ -12,22 +16,142 @@ impl ShipmentSubsRepository {
         Self { pool }
     }
 
-    pub async fn save(&self, shipment_subs: ShipmentSubscription) -> Result<(), Box<dyn Error>> {
-        let _res = sqlx::query(
+    /// stores the subscription and queues its notifications in the same transaction
+    pub async fn save(
+        &self,
+        shipment_subs: ShipmentSubscription,
+        outbox: &[OutboxMessage],
+    ) -> Result<(), Option<TrackingError>> {
+        let mut tx = self.pool.begin().await.map_err(|e| self.handle_db_err(e))?;
+
+        sqlx::query(
             "INSERT INTO  shipment_subscriptions (
                                      user_id, shipment_id,
-                                     subscribed_statuses, label, created_at,
-                                     updated_at) VALUES ($1, $2, $3, $4, $5, $6)",
+                                     subscribed_statuses, notify_on, label, created_at,
+                                     updated_at) VALUES ($1, $2, $3, $4, $5, $6, $7)",
         )
         .bind(shipment_subs.user_id)
         .bind(shipment_subs.shipment_id)
         .bind(shipment_subs.subscribed_statues)
+        .bind(shipment_subs.notify_on)
         .bind(shipment_subs.label)
         .bind(shipment_subs.created_at)
         .bind(shipment_subs.updated_at)
-        .execute(&self.pool)
-        .await?;
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| self.handle_db_err(e))?;
+
+        OutboxRepository::insert(&mut tx, outbox)
+            .await
+            .map_err(|e| self.handle_db_err(e))?;
+
+        tx.commit().await.map_err(|e| self.handle_db_err(e))?;
 
         Ok(())
     }
+
+    pub async fn find_by_shipment(
+        &self,
+        shipment_id: Uuid,
+    ) -> Result<Vec<ShipmentSubscription>, Box<dyn Error>> {
+        let subs = sqlx::query_as::<_, ShipmentSubscription>(
+            "SELECT id, user_id, shipment_id, subscribed_statuses, notify_on,
+                    label, created_at, updated_at
+                FROM shipment_subscriptions WHERE shipment_id = $1",
+        )
+        .bind(shipment_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(subs)
+    }
+
+    pub async fn find_by_user_and_shipment(
+        &self,
+        user_id: Uuid,
+        shipment_id: Uuid,
+    ) -> Result<Option<ShipmentSubscription>, Box<dyn Error>> {
+        let subs = sqlx::query_as::<_, ShipmentSubscription>(
+            "SELECT id, user_id, shipment_id, subscribed_statuses, notify_on,
+                    label, created_at, updated_at
+                FROM shipment_subscriptions WHERE user_id = $1 AND shipment_id = $2",
+        )
+        .bind(user_id)
+        .bind(shipment_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(subs)
+    }
+
+    /// removes the user's subscription. when nobody else follows the shipment,
+    /// its tracking job is deactivated and the shipment itself is deleted
+    pub async fn unsubscribe(
+        &self,
+        user_id: Uuid,
+        shipment_id: Uuid,
+    ) -> Result<UnsubscribeOutcome, Box<dyn Error>> {
+        let mut tx = self.pool.begin().await?;
+
+        // lock the shipment first so a concurrent subscribe can't slip in
+        // between counting the remaining subscribers and deleting the shipment
+        sqlx::query("SELECT id FROM shipments WHERE id = $1 FOR UPDATE")
+            .bind(shipment_id)
+            .execute(&mut *tx)
+            .await?;
+
+        let deleted = sqlx::query(
+            "DELETE FROM shipment_subscriptions WHERE user_id = $1 AND shipment_id = $2",
+        )
+        .bind(user_id)
+        .bind(shipment_id)
+        .execute(&mut *tx)
+        .await?;
+
+        if deleted.rows_affected() == 0 {
+            tx.rollback().await?;
+            return Ok(UnsubscribeOutcome::NotSubscribed);
+        }
+
+        let remaining: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM shipment_subscriptions WHERE shipment_id = $1",
+        )
+        .bind(shipment_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        if remaining > 0 {
+            tx.commit().await?;
+            return Ok(UnsubscribeOutcome::Unsubscribed);
+        }
+
+        sqlx::query(
+            "UPDATE tracking_jobs SET is_active = false, updated_at = now() WHERE shipment_id = $1",
+        )
+        .bind(shipment_id)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query("DELETE FROM shipments WHERE id = $1")
+            .bind(shipment_id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(UnsubscribeOutcome::ShipmentRemoved)
+    }
+
+    fn handle_db_err(&self, e: sqlx::Error) -> Option<TrackingError> {
+        if let Some(db_err) = e.as_database_error() {
+            match db_err.code().map(|c| c.to_string()).as_deref() {
+                Some("23505") => return Some(TrackingError::AlreadySubscribed),
+                Some("23503") => return Some(TrackingError::NotFound),
+                _ => {}
+            }
+        }
+
+        tracing::error!("Internal DB Error: {:?}", e);
+        None
+    }
 }
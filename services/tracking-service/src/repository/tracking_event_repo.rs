@@ -0,0 +1,99 @@
+use crate::models::event::TrackingEvent;
+use sqlx::{Pool, Postgres};
+use std::error::Error;
+use uuid::Uuid;
+
+#[derive(Clone)]
+pub struct TrackingEventRepository {
+    pub pool: Pool<Postgres>,
+}
+
+impl TrackingEventRepository {
+    pub async fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+
+    /// inserts the event unless the same (shipment, raw status, occurred_at) is already stored.
+    /// returns whether a new row was written
+    pub async fn save(&self, event: TrackingEvent) -> Result<bool, Box<dyn Error>> {
+        let res = sqlx::query(
+            "INSERT INTO tracking_events
+                (id, shipment_id, raw_status, normalized_status,
+                 description, location, occurred_at, source, created_at)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                ON CONFLICT (shipment_id, raw_status, occurred_at) DO NOTHING",
+        )
+        .bind(event.id)
+        .bind(event.shipment_id)
+        .bind(event.raw_status)
+        .bind(event.normalized_status)
+        .bind(event.description)
+        .bind(event.location)
+        .bind(event.occurred_at)
+        .bind(event.source)
+        .bind(event.created_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(res.rows_affected() > 0)
+    }
+
+    /// events of a shipment ordered by occurrence, starting right after the `cursor` event
+    pub async fn find_by_shipment(
+        &self,
+        shipment_id: Uuid,
+        cursor: Option<Uuid>,
+        limit: i64,
+    ) -> Result<Vec<TrackingEvent>, Box<dyn Error>> {
+        let events = sqlx::query_as::<_, TrackingEvent>(
+            "SELECT id, shipment_id, raw_status, normalized_status, description,
+                    location, occurred_at, source, created_at
+                FROM tracking_events
+                WHERE shipment_id = $1
+                  AND ($2::uuid IS NULL OR (occurred_at, id) > (
+                      SELECT occurred_at, id FROM tracking_events
+                      WHERE id = $2 AND shipment_id = $1))
+                ORDER BY occurred_at, id
+                LIMIT $3",
+        )
+        .bind(shipment_id)
+        .bind(cursor)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(events)
+    }
+
+    /// whether the event exists and belongs to the shipment
+    pub async fn belongs_to(&self, id: Uuid, shipment_id: Uuid) -> Result<bool, Box<dyn Error>> {
+        let found = sqlx::query_scalar::<_, bool>(
+            "SELECT EXISTS (SELECT 1 FROM tracking_events WHERE id = $1 AND shipment_id = $2)",
+        )
+        .bind(id)
+        .bind(shipment_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(found)
+    }
+
+    pub async fn find_latest(
+        &self,
+        shipment_id: Uuid,
+    ) -> Result<Option<TrackingEvent>, Box<dyn Error>> {
+        let event = sqlx::query_as::<_, TrackingEvent>(
+            "SELECT id, shipment_id, raw_status, normalized_status, description,
+                    location, occurred_at, source, created_at
+                FROM tracking_events
+                WHERE shipment_id = $1
+                ORDER BY occurred_at DESC, id DESC
+                LIMIT 1",
+        )
+        .bind(shipment_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(event)
+    }
+}
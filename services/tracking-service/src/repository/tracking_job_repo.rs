@@ -0,0 +1,113 @@
+use crate::models::job::TrackingJob;
+use chrono::{DateTime, Utc};
+use sqlx::{Pool, Postgres};
+use std::error::Error;
+use uuid::Uuid;
+
+#[derive(Clone)]
+pub struct TrackingJobRepository {
+    pub pool: Pool<Postgres>,
+}
+
+impl TrackingJobRepository {
+    pub async fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+
+    pub async fn create(
+        &self,
+        shipment_id: Uuid,
+        next_run_at: DateTime<Utc>,
+    ) -> Result<(), Box<dyn Error>> {
+        sqlx::query(
+            "INSERT INTO tracking_jobs (shipment_id, next_run_at)
+                VALUES ($1, $2) ON CONFLICT (shipment_id) DO NOTHING",
+        )
+        .bind(shipment_id)
+        .bind(next_run_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// makes sure the shipment is polled again from `next_run_at`. a job that was
+    /// given up on starts over with a clean attempt count, an active one is left alone
+    pub async fn reactivate(
+        &self,
+        shipment_id: Uuid,
+        next_run_at: DateTime<Utc>,
+    ) -> Result<(), Box<dyn Error>> {
+        sqlx::query(
+            "INSERT INTO tracking_jobs (shipment_id, next_run_at)
+                VALUES ($1, $2)
+                ON CONFLICT (shipment_id) DO UPDATE
+                    SET is_active = true, attempt = 0, next_run_at = $2, updated_at = now()
+                    WHERE NOT tracking_jobs.is_active",
+        )
+        .bind(shipment_id)
+        .bind(next_run_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// claims up to `limit` due jobs and pushes their `next_run_at` to `lease_until`,
+    /// so other replicas skip them while this one is working on them.
+    /// if the worker dies mid-way, the job simply becomes due again once the lease expires
+    pub async fn claim_due(
+        &self,
+        limit: i64,
+        lease_until: DateTime<Utc>,
+    ) -> Result<Vec<TrackingJob>, Box<dyn Error>> {
+        let jobs = sqlx::query_as::<_, TrackingJob>(
+            "UPDATE tracking_jobs SET next_run_at = $2, updated_at = now()
+                WHERE shipment_id IN (
+                    SELECT shipment_id FROM tracking_jobs
+                    WHERE is_active AND next_run_at <= now()
+                    ORDER BY next_run_at
+                    LIMIT $1
+                    FOR UPDATE SKIP LOCKED
+                )
+                RETURNING shipment_id, next_run_at, interval_minutes, attempt, is_active, updated_at",
+        )
+        .bind(limit)
+        .bind(lease_until)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(jobs)
+    }
+
+    pub async fn reschedule(
+        &self,
+        shipment_id: Uuid,
+        next_run_at: DateTime<Utc>,
+        attempt: i32,
+    ) -> Result<(), Box<dyn Error>> {
+        sqlx::query(
+            "UPDATE tracking_jobs SET next_run_at = $2, attempt = $3, updated_at = now()
+                WHERE shipment_id = $1",
+        )
+        .bind(shipment_id)
+        .bind(next_run_at)
+        .bind(attempt)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn deactivate(&self, shipment_id: Uuid) -> Result<(), Box<dyn Error>> {
+        sqlx::query(
+            "UPDATE tracking_jobs SET is_active = false, updated_at = now()
+                WHERE shipment_id = $1",
+        )
+        .bind(shipment_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,89 @@
+use crate::auth::error::AuthError;
+use crate::models::user::User;
+use sqlx::{Pool, Postgres};
+use std::error::Error;
+use uuid::Uuid;
+
+#[derive(Clone)]
+pub struct UserRepository {
+    pub pool: Pool<Postgres>,
+}
+
+impl UserRepository {
+    pub async fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+
+    pub async fn save(&self, user: User) -> Result<User, Option<AuthError>> {
+        let user = sqlx::query_as::<_, User>(
+            "INSERT INTO users
+                (id, name, phone_number, email, telegram_chat_id, password_hash, role, created_at)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                RETURNING id, name, phone_number, email, telegram_chat_id, password_hash,
+                          role, created_at",
+        )
+        .bind(user.id)
+        .bind(user.name)
+        .bind(user.phone_number)
+        .bind(user.email)
+        .bind(user.telegram_chat_id)
+        .bind(user.password_hash)
+        .bind(user.role)
+        .bind(user.created_at)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| self.handle_db_err(e))?;
+
+        Ok(user)
+    }
+
+    pub async fn find_by_id(&self, id: Uuid) -> Result<Option<User>, Box<dyn Error>> {
+        let user = sqlx::query_as::<_, User>(
+            "SELECT id, name, phone_number, email, telegram_chat_id, password_hash, role,
+                    created_at
+                FROM users WHERE id = $1",
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(user)
+    }
+
+    pub async fn find_by_ids(&self, ids: &[Uuid]) -> Result<Vec<User>, Box<dyn Error>> {
+        let users = sqlx::query_as::<_, User>(
+            "SELECT id, name, phone_number, email, telegram_chat_id, password_hash, role,
+                    created_at
+                FROM users WHERE id = ANY($1)",
+        )
+        .bind(ids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(users)
+    }
+
+    pub async fn find_by_email(&self, email: &str) -> Result<Option<User>, Box<dyn Error>> {
+        let user = sqlx::query_as::<_, User>(
+            "SELECT id, name, phone_number, email, telegram_chat_id, password_hash, role,
+                    created_at
+                FROM users WHERE email = $1",
+        )
+        .bind(email)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(user)
+    }
+
+    fn handle_db_err(&self, e: sqlx::Error) -> Option<AuthError> {
+        if let Some(db_err) = e.as_database_error()
+            && db_err.code().as_deref() == Some("23505")
+        {
+            return Some(AuthError::UserAlreadyExists);
+        }
+
+        tracing::error!("Internal DB Error: {:?}", e);
+        None
+    }
+}
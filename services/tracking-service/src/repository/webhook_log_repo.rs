@@ -0,0 +1,131 @@
+use crate::models::webhook::{WebhookLog, WebhookReplayFilter};
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use sqlx::{Pool, Postgres};
+use std::error::Error;
+use uuid::Uuid;
+
+#[derive(Clone)]
+pub struct WebhookLogRepository {
+    pub pool: Pool<Postgres>,
+}
+
+impl WebhookLogRepository {
+    pub async fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+
+    pub async fn save(&self, payload: &Value) -> Result<WebhookLog, Box<dyn Error>> {
+        let log = sqlx::query_as::<_, WebhookLog>(
+            "INSERT INTO webhook_logs (id, payload) VALUES ($1, $2)
+                RETURNING id, payload, processed_at, attempts, last_error,
+                          last_attempt_at, created_at",
+        )
+        .bind(Uuid::new_v4())
+        .bind(payload)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(log)
+    }
+
+    pub async fn mark_processed(&self, id: Uuid) -> Result<(), Box<dyn Error>> {
+        sqlx::query(
+            "UPDATE webhook_logs
+                SET processed_at = now(), attempts = attempts + 1,
+                    last_error = NULL, last_attempt_at = now()
+                WHERE id = $1",
+        )
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn record_failure(&self, id: Uuid, error: &str) -> Result<(), Box<dyn Error>> {
+        sqlx::query(
+            "UPDATE webhook_logs
+                SET attempts = attempts + 1, last_error = $2, last_attempt_at = now()
+                WHERE id = $1",
+        )
+        .bind(id)
+        .bind(error)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// claims unprocessed logs that still have attempts left and weren't tried since
+    /// `retry_before`. bumping `last_attempt_at` keeps other replicas off them meanwhile
+    pub async fn claim_unprocessed(
+        &self,
+        limit: i64,
+        max_attempts: i32,
+        retry_before: DateTime<Utc>,
+    ) -> Result<Vec<WebhookLog>, Box<dyn Error>> {
+        let logs = sqlx::query_as::<_, WebhookLog>(
+            "UPDATE webhook_logs SET last_attempt_at = now()
+                WHERE id IN (
+                    SELECT id FROM webhook_logs
+                    WHERE processed_at IS NULL
+                      AND attempts < $2
+                      AND (last_attempt_at IS NULL OR last_attempt_at < $3)
+                    ORDER BY created_at
+                    LIMIT $1
+                    FOR UPDATE SKIP LOCKED
+                )
+                RETURNING id, payload, processed_at, attempts, last_error,
+                          last_attempt_at, created_at",
+        )
+        .bind(limit)
+        .bind(max_attempts)
+        .bind(retry_before)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(logs)
+    }
+
+    pub async fn find_for_replay(
+        &self,
+        filter: &WebhookReplayFilter,
+        limit: i64,
+    ) -> Result<Vec<WebhookLog>, Box<dyn Error>> {
+        let logs = sqlx::query_as::<_, WebhookLog>(
+            "SELECT id, payload, processed_at, attempts, last_error, last_attempt_at, created_at
+                FROM webhook_logs
+                WHERE ($1::uuid[] IS NULL OR id = ANY($1))
+                  AND ($2::timestamptz IS NULL OR created_at >= $2)
+                  AND ($3::timestamptz IS NULL OR created_at < $3)
+                  AND ($4 OR processed_at IS NULL)
+                ORDER BY created_at
+                LIMIT $5",
+        )
+        .bind(filter.ids.clone())
+        .bind(filter.from)
+        .bind(filter.to)
+        .bind(filter.include_processed)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(logs)
+    }
+
+    pub async fn find_failed(&self, limit: i64) -> Result<Vec<WebhookLog>, Box<dyn Error>> {
+        let logs = sqlx::query_as::<_, WebhookLog>(
+            "SELECT id, payload, processed_at, attempts, last_error, last_attempt_at, created_at
+                FROM webhook_logs
+                WHERE processed_at IS NULL AND last_error IS NOT NULL
+                ORDER BY created_at DESC
+                LIMIT $1",
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(logs)
+    }
+}
@@ -1,11 +1,55 @@
 use crate::app::AppState;
-use crate::handlers::tracking::create_shipments;
-use axum::routing::post;
-use axum::Router;
+use crate::auth::middleware::{require_admin, require_auth};
+use crate::handlers::auth::{login, logout, refresh, register};
+use crate::handlers::notification::{get_preferences, update_preferences};
+use crate::handlers::provider::get_rate_limits;
+use crate::handlers::tracking::{
+    create_shipments, delete_shipment_by_id, get_shipment_by_id, get_shipment_events,
+    get_shipments,
+};
+use crate::handlers::webhook::{biteship_webhook, get_failed_webhooks, replay_webhooks};
+use axum::routing::{get, post};
+use axum::{Router, middleware};
 use std::sync::Arc;
 
 pub fn routes(state: Arc<AppState>) -> Router {
-    Router::new()
-        .route("/shipments", post(create_shipments))
-        .with_state(state)
+    let protected = Router::new()
+        .route("/shipments", post(create_shipments).get(get_shipments))
+        .route(
+            "/shipments/{id}",
+            get(get_shipment_by_id).delete(delete_shipment_by_id),
+        )
+        .route("/shipments/{id}/events", get(get_shipment_events))
+        .route(
+            "/notifications/preferences",
+            get(get_preferences).put(update_preferences),
+        )
+        .route_layer(middleware::from_fn_with_state(state.clone(), require_auth));
+
+    // route layers run outside in, the token is checked before the role
+    let admin = Router::new()
+        .route("/admin/webhooks/failed", get(get_failed_webhooks))
+        .route("/admin/webhooks/replay", post(replay_webhooks))
+        .route("/admin/providers/rate-limits", get(get_rate_limits))
+        .route_layer(middleware::from_fn(require_admin))
+        .route_layer(middleware::from_fn_with_state(state.clone(), require_auth));
+
+    let mut public = Router::new()
+        .route("/auth/register", post(register))
+        .route("/auth/login", post(login))
+        .route("/auth/refresh", post(refresh))
+        .route("/auth/logout", post(logout));
+
+    // biteship authenticates with the webhook signature instead of a bearer token,
+    // without one configured there's no way to tell its calls from anyone else's
+    if state.webhook_service.verifier.is_some() {
+        public = public.route("/webhooks/biteship", post(biteship_webhook));
+    } else {
+        tracing::warn!(
+            "BITESHIP_WEBHOOK_SIGNATURE_KEY / BITESHIP_WEBHOOK_SIGNATURE_SECRET not set, \
+             webhooks are disabled"
+        );
+    }
+
+    protected.merge(admin).merge(public).with_state(state)
 }
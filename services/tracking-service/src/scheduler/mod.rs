@@ -0,0 +1,13 @@
+use std::env;
+use std::str::FromStr;
+
+pub mod outbox_relay;
+pub mod tracking_scheduler;
+pub mod webhook_replayer;
+
+pub(crate) fn env_or<T: FromStr>(key: &str, default: T) -> T {
+    env::var(key)
+        .ok()
+        .and_then(|v| v.parse::<T>().ok())
+        .unwrap_or(default)
+}
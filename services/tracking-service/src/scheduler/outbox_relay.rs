@@ -0,0 +1,116 @@
+use crate::repository::outbox_repo::OutboxRepository;
+use crate::scheduler::env_or;
+use crate::service::tracking_service::TrackingService;
+use chrono::{Duration, Utc};
+
+#[derive(Clone, Debug)]
+pub struct OutboxRelayConfig {
+    /// how often pending outbox messages are looked up
+    pub tick_secs: u64,
+    /// max messages published per tick
+    pub batch_size: i64,
+    /// how long a claimed message stays hidden before it's retried
+    pub lease_secs: i64,
+    /// relay publishes after which a message is parked in the table for a human to look at
+    pub max_attempts: i32,
+}
+
+impl OutboxRelayConfig {
+    pub fn from_env() -> Self {
+        Self {
+            tick_secs: env_or("OUTBOX_RELAY_TICK_SECS", 10),
+            batch_size: env_or("OUTBOX_RELAY_BATCH_SIZE", 100),
+            lease_secs: env_or("OUTBOX_RELAY_LEASE_SECS", 60),
+            max_attempts: env_or("OUTBOX_RELAY_MAX_ATTEMPTS", 20),
+        }
+    }
+}
+
+/// publishes outbox messages that couldn't be published when their status change
+/// was committed, e.g. while rabbitmq was down
+#[derive(Clone)]
+pub struct OutboxRelay {
+    service: TrackingService,
+    outbox_repo: OutboxRepository,
+    config: OutboxRelayConfig,
+}
+
+impl OutboxRelay {
+    pub async fn new(
+        service: TrackingService,
+        outbox_repo: OutboxRepository,
+        config: OutboxRelayConfig,
+    ) -> Self {
+        Self {
+            service,
+            outbox_repo,
+            config,
+        }
+    }
+
+    pub async fn run(&self) {
+        tracing::info!(
+            "outbox relay started, tick every {}s",
+            self.config.tick_secs
+        );
+
+        let mut interval =
+            tokio::time::interval(std::time::Duration::from_secs(self.config.tick_secs));
+
+        loop {
+            interval.tick().await;
+            self.tick().await;
+        }
+    }
+
+    async fn tick(&self) {
+        let lease_until = Utc::now() + Duration::seconds(self.config.lease_secs);
+
+        let messages = match self
+            .outbox_repo
+            .claim_due(
+                self.config.batch_size,
+                lease_until,
+                self.config.max_attempts,
+            )
+            .await
+        {
+            Ok(messages) => messages,
+            Err(e) => {
+                tracing::error!("failed to claim outbox messages: {}", e);
+                return;
+            }
+        };
+
+        if messages.is_empty() {
+            return;
+        }
+
+        let mut published = 0;
+        for msg in &messages {
+            if let Err(e) = self.service.publish_outbox_message(msg).await {
+                if msg.attempts >= self.config.max_attempts {
+                    // parked, skip it so it doesn't hold up the rest
+                    tracing::error!(
+                        "giving up on outbox message {} after {} attempts: {}",
+                        msg.id,
+                        msg.attempts,
+                        e
+                    );
+                    continue;
+                }
+
+                // keep the order, the rest waits for the lease to expire
+                tracing::error!("failed to publish outbox message {}: {}", msg.id, e);
+                break;
+            }
+            published += 1;
+        }
+
+        tracing::info!(
+            "published {} of {} pending outbox messages",
+            published,
+            messages.len()
+        );
+    }
+}
@@ -0,0 +1,168 @@
+use crate::models::job::TrackingJob;
+use crate::repository::tracking_job_repo::TrackingJobRepository;
+use crate::scheduler::env_or;
+use crate::service::tracking_service::TrackingService;
+use chrono::{Duration, Utc};
+
+#[derive(Clone, Debug)]
+pub struct SchedulerConfig {
+    /// how often the scheduler looks for due jobs
+    pub tick_secs: u64,
+    /// max jobs claimed per tick
+    pub batch_size: i64,
+    /// how long a claimed job stays hidden from other replicas
+    pub lease_secs: i64,
+    /// upper bound of the failure backoff
+    pub max_backoff_minutes: i64,
+    /// failed refreshes in a row after which the job is given up on
+    pub max_attempts: i32,
+}
+
+impl SchedulerConfig {
+    pub fn from_env() -> Self {
+        Self {
+            tick_secs: env_or("SCHEDULER_TICK_SECS", 30),
+            batch_size: env_or("SCHEDULER_BATCH_SIZE", 20),
+            lease_secs: env_or("SCHEDULER_LEASE_SECS", 300),
+            max_backoff_minutes: env_or("SCHEDULER_MAX_BACKOFF_MINUTES", 720),
+            max_attempts: env_or("SCHEDULER_MAX_ATTEMPTS", 20),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct TrackingScheduler {
+    service: TrackingService,
+    job_repo: TrackingJobRepository,
+    config: SchedulerConfig,
+}
+
+impl TrackingScheduler {
+    pub async fn new(
+        service: TrackingService,
+        job_repo: TrackingJobRepository,
+        config: SchedulerConfig,
+    ) -> Self {
+        Self {
+            service,
+            job_repo,
+            config,
+        }
+    }
+
+    pub async fn run(&self) {
+        tracing::info!(
+            "tracking scheduler started, tick every {}s",
+            self.config.tick_secs
+        );
+
+        let mut interval =
+            tokio::time::interval(std::time::Duration::from_secs(self.config.tick_secs));
+        // a spread out batch can outlast the tick, don't fire the missed ticks back to back
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        loop {
+            interval.tick().await;
+            self.tick().await;
+        }
+    }
+
+    async fn tick(&self) {
+        let lease_until = Utc::now() + Duration::seconds(self.config.lease_secs);
+
+        let jobs = match self
+            .job_repo
+            .claim_due(self.config.batch_size, lease_until)
+            .await
+        {
+            Ok(jobs) => jobs,
+            Err(e) => {
+                tracing::error!("failed to claim tracking jobs: {}", e);
+                return;
+            }
+        };
+
+        if jobs.is_empty() {
+            return;
+        }
+
+        tracing::debug!("claimed {} tracking jobs", jobs.len());
+
+        // spread the batch over the tick instead of bursting it at the provider
+        let started = tokio::time::Instant::now();
+        let spacing = std::time::Duration::from_secs(self.config.tick_secs) / jobs.len() as u32;
+
+        for (i, job) in jobs.into_iter().enumerate() {
+            tokio::time::sleep_until(started + spacing * i as u32).await;
+            self.process(job).await;
+        }
+    }
+
+    async fn process(&self, job: TrackingJob) {
+        let result = self.service.refresh_shipment(job.shipment_id).await;
+
+        let updated = match result {
+            Ok(status) if status.is_terminal() => {
+                tracing::info!(
+                    "shipment {} reached terminal status {}, deactivating its job",
+                    job.shipment_id,
+                    status
+                );
+                self.job_repo.deactivate(job.shipment_id).await
+            }
+            Ok(_) => {
+                let next_run_at = Utc::now() + Duration::minutes(job.interval_minutes as i64);
+                self.job_repo
+                    .reschedule(job.shipment_id, next_run_at, 0)
+                    .await
+            }
+            Err(e) if e.is_permanent() => {
+                tracing::warn!(
+                    "can't track shipment {}: {}, deactivating its job",
+                    job.shipment_id,
+                    e
+                );
+                self.job_repo.deactivate(job.shipment_id).await
+            }
+            Err(e) if job.attempt + 1 >= self.config.max_attempts => {
+                tracing::error!(
+                    "failed to refresh shipment {} {} times in a row: {}, deactivating its job",
+                    job.shipment_id,
+                    job.attempt + 1,
+                    e
+                );
+                self.job_repo.deactivate(job.shipment_id).await
+            }
+            Err(e) => {
+                let attempt = job.attempt + 1;
+                let next_run_at = Utc::now() + self.backoff(job.interval_minutes, attempt);
+
+                tracing::warn!(
+                    "failed to refresh shipment {} (attempt {}): {}, retrying at {}",
+                    job.shipment_id,
+                    attempt,
+                    e,
+                    next_run_at
+                );
+
+                self.job_repo
+                    .reschedule(job.shipment_id, next_run_at, attempt)
+                    .await
+            }
+        };
+
+        if let Err(e) = updated {
+            tracing::error!("failed to update tracking job {}: {}", job.shipment_id, e);
+        }
+    }
+
+    /// doubles the regular interval for every failed attempt, capped at `max_backoff_minutes`
+    fn backoff(&self, interval_minutes: i32, attempt: i32) -> Duration {
+        let factor = 2i64.saturating_pow(attempt.clamp(0, 16) as u32);
+        let minutes = (interval_minutes as i64)
+            .saturating_mul(factor)
+            .min(self.config.max_backoff_minutes);
+
+        Duration::minutes(minutes)
+    }
+}
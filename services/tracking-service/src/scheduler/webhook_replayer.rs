@@ -0,0 +1,97 @@
+use crate::repository::webhook_log_repo::WebhookLogRepository;
+use crate::scheduler::env_or;
+use crate::service::webhook_service::WebhookService;
+use chrono::{Duration, Utc};
+
+#[derive(Clone, Debug)]
+pub struct WebhookReplayConfig {
+    /// how often unprocessed webhook logs are looked up
+    pub tick_secs: u64,
+    /// max logs replayed per tick
+    pub batch_size: i64,
+    /// logs failing this many times are left for an admin replay
+    pub max_attempts: i32,
+    /// minimum wait between two attempts of the same log
+    pub retry_after_secs: i64,
+}
+
+impl WebhookReplayConfig {
+    pub fn from_env() -> Self {
+        Self {
+            tick_secs: env_or("WEBHOOK_REPLAY_TICK_SECS", 60),
+            batch_size: env_or("WEBHOOK_REPLAY_BATCH_SIZE", 50),
+            max_attempts: env_or("WEBHOOK_REPLAY_MAX_ATTEMPTS", 10),
+            retry_after_secs: env_or("WEBHOOK_REPLAY_RETRY_AFTER_SECS", 300),
+        }
+    }
+}
+
+/// periodically replays webhook logs whose processing failed
+#[derive(Clone)]
+pub struct WebhookReplayer {
+    service: WebhookService,
+    webhook_log_repo: WebhookLogRepository,
+    config: WebhookReplayConfig,
+}
+
+impl WebhookReplayer {
+    pub async fn new(
+        service: WebhookService,
+        webhook_log_repo: WebhookLogRepository,
+        config: WebhookReplayConfig,
+    ) -> Self {
+        Self {
+            service,
+            webhook_log_repo,
+            config,
+        }
+    }
+
+    pub async fn run(&self) {
+        tracing::info!(
+            "webhook replayer started, tick every {}s",
+            self.config.tick_secs
+        );
+
+        let mut interval =
+            tokio::time::interval(std::time::Duration::from_secs(self.config.tick_secs));
+
+        loop {
+            interval.tick().await;
+            self.tick().await;
+        }
+    }
+
+    async fn tick(&self) {
+        let retry_before = Utc::now() - Duration::seconds(self.config.retry_after_secs);
+
+        let logs = match self
+            .webhook_log_repo
+            .claim_unprocessed(
+                self.config.batch_size,
+                self.config.max_attempts,
+                retry_before,
+            )
+            .await
+        {
+            Ok(logs) => logs,
+            Err(e) => {
+                tracing::error!("failed to claim webhook logs: {}", e);
+                return;
+            }
+        };
+
+        if logs.is_empty() {
+            return;
+        }
+
+        let report = self.service.replay(logs).await;
+
+        tracing::info!(
+            "replayed {} webhook logs, {} processed, {} failed",
+            report.total,
+            report.processed,
+            report.failed.len()
+        );
+    }
+}
@@ -0,0 +1,229 @@
+use crate::auth::error::AuthError;
+use crate::auth::jwt::JwtService;
+use crate::auth::password::{hash_password, verify_password};
+use crate::models::dto::{
+    LoginRequest, MessageResponse, RefreshTokenRequest, RegisterRequest, TokenResponse,
+    UserResponse,
+};
+use crate::models::user::{User, UserRole};
+use crate::repository::refresh_token_repo::RefreshTokenRepository;
+use crate::repository::user_repo::UserRepository;
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use chrono::{Duration, Utc};
+use errors::error::HttpError;
+use sha2::{Digest, Sha256};
+use std::env;
+use uuid::Uuid;
+
+static MIN_PASSWORD_LEN: usize = 8;
+
+#[derive(Clone)]
+pub struct AuthService {
+    pub user_repo: UserRepository,
+    pub refresh_token_repo: RefreshTokenRepository,
+    pub jwt: JwtService,
+    /// lifetime of a refresh token in days
+    pub refresh_ttl_days: i64,
+}
+
+impl AuthService {
+    pub async fn new(
+        user_repo: UserRepository,
+        refresh_token_repo: RefreshTokenRepository,
+        jwt: JwtService,
+    ) -> Self {
+        Self {
+            user_repo,
+            refresh_token_repo,
+            jwt,
+            refresh_ttl_days: env::var("JWT_REFRESH_TTL_DAYS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(30),
+        }
+    }
+
+    pub async fn register(&self, req: RegisterRequest) -> Result<UserResponse, HttpError> {
+        let name = req.name.trim().to_string();
+        let email = req.email.trim().to_lowercase();
+        let phone_number = req
+            .phone_number
+            .map(|p| p.trim().to_string())
+            .filter(|p| !p.is_empty());
+        let telegram_chat_id = req
+            .telegram_chat_id
+            .map(|t| t.trim().to_string())
+            .filter(|t| !t.is_empty());
+
+        if name.is_empty() {
+            return Err(HttpError::BadRequest("name is required".into()));
+        }
+        if !email.contains('@') {
+            return Err(HttpError::BadRequest("email is invalid".into()));
+        }
+        if req.password.chars().count() < MIN_PASSWORD_LEN {
+            return Err(HttpError::BadRequest(format!(
+                "password must be at least {} characters",
+                MIN_PASSWORD_LEN
+            )));
+        }
+
+        let password_hash = hash_password(req.password).await?;
+
+        let user = User {
+            id: Uuid::new_v4(),
+            name,
+            phone_number,
+            email: Some(email),
+            telegram_chat_id,
+            password_hash: Some(password_hash),
+            role: UserRole::User,
+            created_at: Utc::now(),
+        };
+
+        let user = self.user_repo.save(user).await.map_err(|e| match e {
+            Some(err) => err.into(),
+            None => HttpError::InternalServerError(anyhow::anyhow!("error from db")),
+        })?;
+
+        Ok(user.into())
+    }
+
+    pub async fn login(&self, req: LoginRequest) -> Result<TokenResponse, HttpError> {
+        let user = self
+            .user_repo
+            .find_by_email(&req.email.trim().to_lowercase())
+            .await
+            .map_err(|e| HttpError::InternalServerError(anyhow::anyhow!(e.to_string())))?
+            .ok_or(AuthError::InvalidCredentials)?;
+
+        // seeded users without a password can't log in
+        let hash = user
+            .password_hash
+            .clone()
+            .ok_or(AuthError::InvalidCredentials)?;
+
+        if !verify_password(req.password, hash).await? {
+            return Err(AuthError::InvalidCredentials.into());
+        }
+
+        let refresh_token = new_refresh_token();
+        self.refresh_token_repo
+            .save(
+                user.id,
+                Uuid::new_v4(),
+                &hash_token(&refresh_token),
+                self.refresh_expiry(),
+            )
+            .await
+            .map_err(|e| HttpError::InternalServerError(anyhow::anyhow!(e.to_string())))?;
+
+        self.token_response(&user, refresh_token)
+    }
+
+    /// trades a refresh token for a new token pair. the old refresh token is revoked,
+    /// and presenting an already rotated token revokes the whole login since it
+    /// means the token leaked
+    pub async fn refresh(&self, req: RefreshTokenRequest) -> Result<TokenResponse, HttpError> {
+        let current = self
+            .refresh_token_repo
+            .find_by_hash(&hash_token(&req.refresh_token))
+            .await
+            .map_err(|e| HttpError::InternalServerError(anyhow::anyhow!(e.to_string())))?
+            .ok_or(AuthError::InvalidRefreshToken)?;
+
+        if current.revoked_at.is_some() {
+            self.revoke_reused(current.family_id, current.user_id)
+                .await?;
+            return Err(AuthError::InvalidRefreshToken.into());
+        }
+
+        if current.expires_at <= Utc::now() {
+            return Err(AuthError::InvalidRefreshToken.into());
+        }
+
+        let refresh_token = new_refresh_token();
+        let rotated = self
+            .refresh_token_repo
+            .rotate(&current, &hash_token(&refresh_token), self.refresh_expiry())
+            .await
+            .map_err(|e| HttpError::InternalServerError(anyhow::anyhow!(e.to_string())))?;
+
+        if rotated.is_none() {
+            // another request rotated the same token first
+            self.revoke_reused(current.family_id, current.user_id)
+                .await?;
+            return Err(AuthError::InvalidRefreshToken.into());
+        }
+
+        // the role is read again so promotions and demotions reach the next token
+        let user = self
+            .user_repo
+            .find_by_id(current.user_id)
+            .await
+            .map_err(|e| HttpError::InternalServerError(anyhow::anyhow!(e.to_string())))?
+            .ok_or(AuthError::InvalidRefreshToken)?;
+
+        self.token_response(&user, refresh_token)
+    }
+
+    /// revokes the login the refresh token belongs to. unknown tokens are ignored
+    pub async fn logout(&self, req: RefreshTokenRequest) -> Result<MessageResponse, HttpError> {
+        let token = self
+            .refresh_token_repo
+            .find_by_hash(&hash_token(&req.refresh_token))
+            .await
+            .map_err(|e| HttpError::InternalServerError(anyhow::anyhow!(e.to_string())))?;
+
+        if let Some(token) = token {
+            self.refresh_token_repo
+                .revoke_family(token.family_id)
+                .await
+                .map_err(|e| HttpError::InternalServerError(anyhow::anyhow!(e.to_string())))?;
+        }
+
+        Ok(MessageResponse {
+            message: "Successfully logged out".into(),
+        })
+    }
+
+    async fn revoke_reused(&self, family_id: Uuid, user_id: Uuid) -> Result<(), HttpError> {
+        tracing::warn!(
+            "refresh token reuse detected for user {}, revoking its login",
+            user_id
+        );
+
+        self.refresh_token_repo
+            .revoke_family(family_id)
+            .await
+            .map_err(|e| HttpError::InternalServerError(anyhow::anyhow!(e.to_string())))
+    }
+
+    fn token_response(
+        &self,
+        user: &User,
+        refresh_token: String,
+    ) -> Result<TokenResponse, HttpError> {
+        Ok(TokenResponse {
+            access_token: self.jwt.issue(user.id, user.role)?,
+            refresh_token,
+            token_type: "Bearer".into(),
+            expires_in: self.jwt.access_ttl_secs,
+        })
+    }
+
+    fn refresh_expiry(&self) -> chrono::DateTime<Utc> {
+        Utc::now() + Duration::days(self.refresh_ttl_days)
+    }
+}
+
+/// opaque random token, only its hash is stored
+fn new_refresh_token() -> String {
+    let bytes: [u8; 32] = rand::random();
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+fn hash_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
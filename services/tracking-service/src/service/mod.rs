@@ -1 +1,4 @@
+pub mod auth_service;
+pub mod preference_service;
 pub mod tracking_service;
+pub mod webhook_service;
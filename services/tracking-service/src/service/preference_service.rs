@@ -0,0 +1,103 @@
+use crate::models::dto::{NotificationPreferenceResponse, UpdateNotificationPreferenceRequest};
+use crate::models::notification::NotificationChannel;
+use crate::models::preference::NotificationPreference;
+use crate::repository::preference_repo::PreferenceRepository;
+use chrono::Utc;
+use chrono_tz::Tz;
+use errors::error::HttpError;
+use uuid::Uuid;
+
+#[derive(Clone)]
+pub struct PreferenceService {
+    pub preference_repo: PreferenceRepository,
+}
+
+impl PreferenceService {
+    pub async fn new(preference_repo: PreferenceRepository) -> Self {
+        Self { preference_repo }
+    }
+
+    pub async fn get_preferences(
+        &self,
+        user_id: Uuid,
+    ) -> Result<NotificationPreferenceResponse, HttpError> {
+        Ok(self.find_or_default(user_id).await?.into())
+    }
+
+    pub async fn update_preferences(
+        &self,
+        user_id: Uuid,
+        req: UpdateNotificationPreferenceRequest,
+    ) -> Result<NotificationPreferenceResponse, HttpError> {
+        let mut pref = self.find_or_default(user_id).await?;
+
+        for (flag, channel) in [
+            (req.whatsapp, NotificationChannel::Whatsapp),
+            (req.email, NotificationChannel::Email),
+            (req.telegram, NotificationChannel::Telegram),
+        ] {
+            match flag {
+                Some(true) if !pref.default_channels.contains(&channel) => {
+                    pref.default_channels.push(channel)
+                }
+                Some(false) => pref.default_channels.retain(|c| *c != channel),
+                _ => {}
+            }
+        }
+
+        if let Some(statuses) = req.notify_on {
+            pref.default_statuses.clear();
+            for status in statuses {
+                if !pref.default_statuses.contains(&status) {
+                    pref.default_statuses.push(status);
+                }
+            }
+        }
+
+        if let Some(quiet_hours) = req.quiet_hours {
+            match quiet_hours {
+                Some(q) if q.start == q.end => {
+                    return Err(HttpError::BadRequest(
+                        "quiet hours must not start and end at the same time".into(),
+                    ));
+                }
+                Some(q) => {
+                    pref.quiet_hours_start = Some(q.start);
+                    pref.quiet_hours_end = Some(q.end);
+                }
+                None => {
+                    pref.quiet_hours_start = None;
+                    pref.quiet_hours_end = None;
+                }
+            }
+        }
+
+        if let Some(timezone) = req.timezone {
+            let tz = timezone
+                .trim()
+                .parse::<Tz>()
+                .map_err(|_| HttpError::BadRequest(format!("unknown timezone {}", timezone)))?;
+            pref.timezone = tz.name().to_string();
+        }
+
+        pref.updated_at = Utc::now();
+
+        let pref = self
+            .preference_repo
+            .upsert(pref)
+            .await
+            .map_err(|e| HttpError::InternalServerError(anyhow::anyhow!(e.to_string())))?;
+
+        Ok(pref.into())
+    }
+
+    async fn find_or_default(&self, user_id: Uuid) -> Result<NotificationPreference, HttpError> {
+        let pref = self
+            .preference_repo
+            .find_by_user(user_id)
+            .await
+            .map_err(|e| HttpError::InternalServerError(anyhow::anyhow!(e.to_string())))?;
+
+        Ok(pref.unwrap_or_else(|| NotificationPreference::defaults(user_id)))
+    }
+}
@@ -1,69 +1,236 @@
-use crate::models::dto::{AddTrackingRequest, AddTrackingResponse};
-use crate::models::notification::{
-    NotificationChannel, TrackingEventMsg, TrackingEventMsgType, TrackingMsgPayload,
+use crate::models::dto::{
+    AddTrackingRequest, AddTrackingResponse, MessageResponse, ShipmentDetailResponse,
+    ShipmentEventListResponse, ShipmentEventsQuery, ShipmentListQuery, ShipmentListResponse,
 };
+use crate::models::event::{TrackingEvent, TrackingEventSource};
+use crate::models::notification::{TrackingEventMsg, TrackingEventMsgType, TrackingMsgPayload};
+use crate::models::outbox::OutboxMessage;
+use crate::models::preference::NotificationPreference;
 use crate::models::shipment::{
-    Shipment, ShipmentSource, ShipmentStatus, ShipmentStatusParse, ShipmentSubscription,
+    Shipment, ShipmentFilter, ShipmentSource, ShipmentStatus, ShipmentStatusParse,
+    ShipmentSubscription, UnsubscribeOutcome,
 };
+use crate::models::user::User;
 use crate::repository::shipment_repo::ShipmentRepository;
 use crate::repository::shipment_status_mapping_repo::ShipmentStatusMappingRepository;
 use crate::repository::shipment_subscription::ShipmentSubsRepository;
+use crate::repository::tracking_event_repo::TrackingEventRepository;
+use crate::repository::tracking_job_repo::TrackingJobRepository;
+use crate::repository::outbox_repo::OutboxRepository;
+use crate::repository::preference_repo::PreferenceRepository;
+use crate::repository::user_repo::UserRepository;
 use anyhow::anyhow;
-use biteship::BiteshipUseCase;
 use chrono::Utc;
+use config::rabbitmq::topology::NOTIFICATION_EXCHANGE;
 use errors::error::HttpError;
 use lapin::BasicProperties;
 use lapin::options::BasicPublishOptions;
-use std::str::FromStr;
+use provider::dto::{TrackingCheckpoint, TrackingSnapshot};
+use provider::error::ProviderError;
+use provider::registry::ProviderRegistry;
+use std::collections::HashMap;
+use thiserror::Error;
 use uuid::Uuid;
 
-static EXCHANGE_NAME: &str = "notification.events";
+static DEFAULT_SHIPMENT_PAGE_SIZE: i64 = 10;
+static DEFAULT_EVENT_PAGE_SIZE: i64 = 20;
+static MAX_PAGE_SIZE: i64 = 100;
+
+/// why a refresh failed, the provider's answer is kept apart so the scheduler can
+/// tell a waybill that will never resolve from a hiccup on our side
+#[derive(Debug, Error)]
+pub enum RefreshError {
+    #[error(transparent)]
+    Provider(#[from] ProviderError),
+
+    #[error("shipment not found")]
+    ShipmentNotFound,
+
+    #[error(transparent)]
+    Internal(#[from] HttpError),
+}
+
+impl RefreshError {
+    /// retrying won't help, the job can be given up on
+    pub fn is_permanent(&self) -> bool {
+        match self {
+            RefreshError::Provider(e) => e.is_permanent(),
+            RefreshError::ShipmentNotFound => true,
+            RefreshError::Internal(_) => false,
+        }
+    }
+}
 
 #[derive(Clone)]
 pub struct TrackingService {
     pub shipment_repository: ShipmentRepository,
     pub shipment_subs_repo: ShipmentSubsRepository,
     pub map_status_repo: ShipmentStatusMappingRepository,
-    pub biteship_uc: BiteshipUseCase,
+    pub tracking_job_repo: TrackingJobRepository,
+    pub tracking_event_repo: TrackingEventRepository,
+    pub user_repo: UserRepository,
+    pub preference_repo: PreferenceRepository,
+    pub outbox_repo: OutboxRepository,
+    pub providers: ProviderRegistry,
     pub rabbitmq_channel: lapin::Channel,
 }
 
 impl TrackingService {
+    #[allow(clippy::too_many_arguments)]
     pub async fn new(
         shipment_repository: ShipmentRepository,
         shipment_subs_repo: ShipmentSubsRepository,
         map_status_repo: ShipmentStatusMappingRepository,
-        biteship_uc: BiteshipUseCase,
+        tracking_job_repo: TrackingJobRepository,
+        tracking_event_repo: TrackingEventRepository,
+        user_repo: UserRepository,
+        preference_repo: PreferenceRepository,
+        outbox_repo: OutboxRepository,
+        providers: ProviderRegistry,
         rabbitmq_channel: lapin::Channel,
     ) -> Self {
         Self {
             shipment_repository,
             shipment_subs_repo,
             map_status_repo,
-            biteship_uc,
+            tracking_job_repo,
+            tracking_event_repo,
+            user_repo,
+            preference_repo,
+            outbox_repo,
+            providers,
             rabbitmq_channel,
         }
     }
 
     pub async fn add_track(
         &self,
+        user_uuid: Uuid,
         req: &AddTrackingRequest,
     ) -> Result<AddTrackingResponse, HttpError> {
-        let bs_resp = self
-            .biteship_uc
-            .fetch_public_tracking(req.awb.clone(), req.courier_code.clone())
-            .await?;
+        let shipment = self.resolve_shipment(req).await?;
+        let current_time = Utc::now();
+
+        let pref = self
+            .preference_repo
+            .find_by_user(user_uuid)
+            .await
+            .map_err(|e| HttpError::InternalServerError(anyhow::anyhow!(e.to_string())))?
+            .unwrap_or_else(|| NotificationPreference::defaults(user_uuid));
+
+        let notify_on = req
+            .notify_on
+            .clone()
+            .unwrap_or(pref.default_channels);
+
+        let subs = ShipmentSubscription {
+            id: Uuid::new_v4(),
+            user_id: user_uuid,
+            shipment_id: shipment.id,
+            subscribed_statues: pref.default_statuses,
+            notify_on: notify_on.clone(),
+            label: req.label.clone(),
+            created_at: current_time,
+            updated_at: current_time,
+        };
+
+        let user = self
+            .user_repo
+            .find_by_id(user_uuid)
+            .await
+            .map_err(|e| HttpError::InternalServerError(anyhow::anyhow!(e.to_string())))?
+            .ok_or_else(|| HttpError::Unauthorized("user no longer exists".to_string()))?;
+
+        let mut scheduled_channels = Vec::new();
+        let mut skipped_channels = Vec::new();
+        let mut outbox = Vec::new();
+        let correlation_id = Uuid::new_v4();
+
+        for ch in notify_on.iter() {
+            let Some(recipient) = user.contact_for(ch) else {
+                skipped_channels.push(ch.clone());
+                continue;
+            };
+
+            let msg = TrackingEventMsg {
+                message_id: Uuid::new_v4(),
+                event_type: TrackingEventMsgType::TrackingAdded,
+                channel: ch.clone(),
+                user_id: user_uuid,
+                recipient: recipient.to_string(),
+                template_code: "TRACKING_STATUS".to_string(),
+                payload: TrackingMsgPayload {
+                    waybill_id: req.awb.clone(),
+                    status: shipment.current_status.to_string().to_lowercase(),
+                    courier: shipment.courier_code.clone(),
+                },
+            };
+
+            let msg = OutboxMessage::tracking_event(msg, correlation_id).map_err(|e| {
+                HttpError::InternalServerError(anyhow!("failed to serialize msg payload: {}", e))
+            })?;
+            outbox.push(msg);
+            scheduled_channels.push(ch.clone());
+        }
+
+        // queued with the subscription, so a rabbitmq outage can't lose them
+        self.shipment_subs_repo
+            .save(subs, &outbox)
+            .await
+            .map_err(|e| match e {
+                Some(err) => err.into(),
+                None => HttpError::InternalServerError(anyhow::anyhow!("error from db")),
+            })?;
+
+        self.publish_pending(&outbox).await;
+
+        let response = AddTrackingResponse {
+            message: "Successfully add new tracking".into(),
+            scheduled_channels,
+            skipped_channels,
+        };
+
+        Ok(response)
+    }
+
+    /// returns the stored shipment for the requested waybill, or fetches it from the
+    /// provider and stores it when nobody tracks it yet. a waybill is stored once and
+    /// shared by all of its subscribers
+    async fn resolve_shipment(&self, req: &AddTrackingRequest) -> Result<Shipment, HttpError> {
+        let existing = self
+            .shipment_repository
+            .find_by_waybill(&req.awb, &req.courier_code)
+            .await
+            .map_err(|e| HttpError::InternalServerError(anyhow::anyhow!(e.to_string())))?;
+
+        if let Some(shipment) = existing {
+            // its job may have been given up on, e.g. after too many failed polls,
+            // the new subscriber shouldn't be left with a frozen status
+            if !shipment.current_status.is_terminal() {
+                self.tracking_job_repo
+                    .reactivate(shipment.id, Utc::now())
+                    .await
+                    .map_err(|e| HttpError::InternalServerError(anyhow::anyhow!(e.to_string())))?;
+            }
+
+            return Ok(shipment);
+        }
 
         let external = match req.is_internal {
             true => ShipmentSource::Internal,
             false => ShipmentSource::External,
         };
 
+        let provider = self.providers.resolve(&req.courier_code)?;
+        let snapshot = provider
+            .fetch_tracking(&req.awb, &req.courier_code, external.tenant())
+            .await?;
+
         let current_time = Utc::now();
 
         let status = self
             .map_status_repo
-            .map_external_status(bs_resp.status.as_str())
+            .map_external_status(provider.name(), snapshot.status.as_str())
             .await
             .map_err(|e| HttpError::InternalServerError(anyhow::anyhow!(e.to_string())))?;
 
@@ -78,87 +245,456 @@ impl TrackingService {
             created_at: current_time,
             updated_at: current_time,
         };
-        let shipment_id_clone = shipment.id.clone();
 
-        self.shipment_repository
-            .save(shipment.clone())
+        // another request may have stored the same waybill in the meantime,
+        // in which case that row is returned instead of ours
+        let shipment = self
+            .shipment_repository
+            .upsert(shipment)
             .await
             .map_err(|e| match e {
                 Some(err) => HttpError::BadRequest(err.to_string()),
                 None => HttpError::InternalServerError(anyhow::anyhow!("error from db")),
             })?;
 
-        let user_uuid = Uuid::from_str("550e8400-e29b-41d4-a716-446655440000").unwrap();
-        let subs = ShipmentSubscription {
-            id: Uuid::new_v4(),
-            // this is a dummy user for the development phase
-            user_id: user_uuid,
-            shipment_id: shipment_id_clone,
-            subscribed_statues: vec![
-                ShipmentStatus::InTransit,
-                ShipmentStatus::OutForDelivery,
-                ShipmentStatus::Delivered,
-                ShipmentStatus::Delivered,
-            ],
-            label: req.label.clone(),
-            created_at: current_time,
-            updated_at: current_time,
+        self.record_history(
+            &shipment,
+            provider.name(),
+            &snapshot.history,
+            TrackingEventSource::Polling,
+        )
+        .await?;
+
+        if !shipment.current_status.is_terminal() {
+            self.tracking_job_repo
+                .create(shipment.id, current_time)
+                .await
+                .map_err(|e| HttpError::InternalServerError(anyhow::anyhow!(e.to_string())))?;
+        }
+
+        Ok(shipment)
+    }
+
+    /// re-fetches the shipment from the provider and stores its latest normalized status.
+    /// returns the status the shipment ended up in
+    pub async fn refresh_shipment(
+        &self,
+        shipment_id: Uuid,
+    ) -> Result<ShipmentStatus, RefreshError> {
+        let shipment = self
+            .shipment_repository
+            .find_by_id(shipment_id)
+            .await
+            .map_err(|e| HttpError::InternalServerError(anyhow::anyhow!(e.to_string())))?
+            .ok_or(RefreshError::ShipmentNotFound)?;
+
+        let snapshot = self
+            .providers
+            .resolve(&shipment.courier_code)?
+            .fetch_tracking(
+                &shipment.waybill_id,
+                &shipment.courier_code,
+                shipment.source.tenant(),
+            )
+            .await?;
+
+        Ok(self.apply_tracking(&shipment, &snapshot).await?)
+    }
+
+    /// compares a freshly fetched tracking response against the stored shipment.
+    /// when the normalized status moved, the row is updated and every subscriber
+    /// interested in the new status gets a `tracking.status_updated` event on each
+    /// of their channels. returns the status the shipment ended up in
+    pub async fn apply_tracking(
+        &self,
+        shipment: &Shipment,
+        snapshot: &TrackingSnapshot,
+    ) -> Result<ShipmentStatus, HttpError> {
+        self.record_history(
+            shipment,
+            &snapshot.provider,
+            &snapshot.history,
+            TrackingEventSource::Polling,
+        )
+        .await?;
+
+        self.apply_status(shipment, &snapshot.provider, snapshot.status.as_str())
+            .await
+    }
+
+    /// normalizes `raw_status` and, when it differs from the stored one, moves the
+    /// shipment to it and notifies the interested subscribers
+    pub async fn apply_status(
+        &self,
+        shipment: &Shipment,
+        platform: &str,
+        raw_status: &str,
+    ) -> Result<ShipmentStatus, HttpError> {
+        let status = self
+            .map_status_repo
+            .map_external_status(platform, raw_status)
+            .await
+            .map_err(|e| HttpError::InternalServerError(anyhow::anyhow!(e.to_string())))?;
+
+        if status == shipment.current_status {
+            return Ok(status);
+        }
+
+        // a raw status without a mapping says nothing about where the parcel is,
+        // writing it would flap the shipment and re-notify once the next known one comes
+        if status == ShipmentStatus::Unknown {
+            tracing::warn!(
+                "no status mapping for {} status {}, keeping shipment {} at {}",
+                platform,
+                raw_status,
+                shipment.id,
+                shipment.current_status
+            );
+            return Ok(shipment.current_status.clone());
+        }
+
+        let subscriptions = self
+            .shipment_subs_repo
+            .find_by_shipment(shipment.id)
+            .await
+            .map_err(|e| HttpError::InternalServerError(anyhow::anyhow!(e.to_string())))?;
+
+        let interested: Vec<&ShipmentSubscription> = subscriptions
+            .iter()
+            .filter(|s| s.subscribed_statues.contains(&status))
+            .collect();
+
+        let user_ids: Vec<Uuid> = interested.iter().map(|s| s.user_id).collect();
+        let users: HashMap<Uuid, User> = self
+            .user_repo
+            .find_by_ids(&user_ids)
+            .await
+            .map_err(|e| HttpError::InternalServerError(anyhow::anyhow!(e.to_string())))?
+            .into_iter()
+            .map(|u| (u.id, u))
+            .collect();
+
+        let correlation_id = Uuid::new_v4();
+        let mut outbox = Vec::new();
+
+        for subs in interested {
+            let Some(user) = users.get(&subs.user_id) else {
+                continue;
+            };
+
+            for ch in subs.notify_on.iter() {
+                let Some(recipient) = user.contact_for(ch) else {
+                    tracing::debug!(
+                        "user {} has no contact for {}, skipping notification",
+                        user.id,
+                        ch
+                    );
+                    continue;
+                };
+
+                let msg = TrackingEventMsg {
+                    message_id: Uuid::new_v4(),
+                    event_type: TrackingEventMsgType::TrackingStatusUpdated,
+                    channel: ch.clone(),
+                    user_id: subs.user_id,
+                    recipient: recipient.to_string(),
+                    template_code: "TRACKING_STATUS".to_string(),
+                    payload: TrackingMsgPayload {
+                        waybill_id: shipment.waybill_id.clone(),
+                        status: status.to_string().to_lowercase(),
+                        courier: shipment.courier_code.clone(),
+                    },
+                };
+
+                let msg = OutboxMessage::tracking_event(msg, correlation_id).map_err(|e| {
+                    HttpError::InternalServerError(anyhow!("failed to serialize msg payload: {}", e))
+                })?;
+                outbox.push(msg);
+            }
+        }
+
+        // polling and webhooks may race on the same shipment, only the one that
+        // actually moves the row queues notifications. they are committed together
+        // with the move, so a failed publish can't lose them
+        let moved = self
+            .shipment_repository
+            .update_status(
+                shipment.id,
+                &shipment.current_status,
+                status.clone(),
+                Utc::now(),
+                &outbox,
+            )
+            .await
+            .map_err(|e| HttpError::InternalServerError(anyhow::anyhow!(e.to_string())))?;
+
+        if !moved {
+            return Ok(status);
+        }
+
+        tracing::info!(
+            "shipment {} moved from {} to {}",
+            shipment.id,
+            shipment.current_status,
+            status
+        );
+
+        self.publish_pending(&outbox).await;
+
+        Ok(status)
+    }
+
+    pub async fn list_shipments(
+        &self,
+        user_id: Uuid,
+        query: &ShipmentListQuery,
+    ) -> Result<ShipmentListResponse, HttpError> {
+        let filter = ShipmentFilter {
+            user_id,
+            status: query.status.clone(),
+            courier_code: query.courier_code.clone(),
+            source: query.source.clone(),
+        };
+
+        let limit = query.limit.unwrap_or(DEFAULT_SHIPMENT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+        let offset = query.offset.unwrap_or(0).max(0);
+
+        let data = self
+            .shipment_repository
+            .find_subscribed(&filter, limit, offset)
+            .await
+            .map_err(|e| HttpError::InternalServerError(anyhow::anyhow!(e.to_string())))?;
+
+        let total = self
+            .shipment_repository
+            .count_subscribed(&filter)
+            .await
+            .map_err(|e| HttpError::InternalServerError(anyhow::anyhow!(e.to_string())))?;
+
+        Ok(ShipmentListResponse {
+            data,
+            total,
+            limit,
+            offset,
+        })
+    }
+
+    pub async fn get_shipment(
+        &self,
+        user_id: Uuid,
+        shipment_id: Uuid,
+    ) -> Result<ShipmentDetailResponse, HttpError> {
+        let shipment = self
+            .shipment_repository
+            .find_by_id(shipment_id)
+            .await
+            .map_err(|e| HttpError::InternalServerError(anyhow::anyhow!(e.to_string())))?
+            .ok_or_else(|| HttpError::NotFound("shipment not found".to_string()))?;
+
+        // shipments the caller doesn't follow are treated as not found
+        let subscription = self
+            .shipment_subs_repo
+            .find_by_user_and_shipment(user_id, shipment_id)
+            .await
+            .map_err(|e| HttpError::InternalServerError(anyhow::anyhow!(e.to_string())))?
+            .ok_or_else(|| HttpError::NotFound("shipment not found".to_string()))?;
+
+        let latest_event = self
+            .tracking_event_repo
+            .find_latest(shipment_id)
+            .await
+            .map_err(|e| HttpError::InternalServerError(anyhow::anyhow!(e.to_string())))?;
+
+        Ok(ShipmentDetailResponse {
+            shipment,
+            subscription,
+            latest_event: latest_event.map(Into::into),
+        })
+    }
+
+    pub async fn delete_shipment(
+        &self,
+        user_id: Uuid,
+        shipment_id: Uuid,
+    ) -> Result<MessageResponse, HttpError> {
+        let outcome = self
+            .shipment_subs_repo
+            .unsubscribe(user_id, shipment_id)
+            .await
+            .map_err(|e| HttpError::InternalServerError(anyhow::anyhow!(e.to_string())))?;
+
+        let message = match outcome {
+            UnsubscribeOutcome::NotSubscribed => {
+                return Err(HttpError::NotFound("shipment not found".to_string()));
+            }
+            UnsubscribeOutcome::Unsubscribed => "Successfully unsubscribed from shipment",
+            UnsubscribeOutcome::ShipmentRemoved => {
+                tracing::info!("shipment {} has no subscribers left, removed", shipment_id);
+                "Successfully deleted shipment"
+            }
         };
 
+        Ok(MessageResponse {
+            message: message.into(),
+        })
+    }
+
+    pub async fn get_shipment_events(
+        &self,
+        user_id: Uuid,
+        shipment_id: Uuid,
+        query: &ShipmentEventsQuery,
+    ) -> Result<ShipmentEventListResponse, HttpError> {
+        // shipments the caller doesn't follow are treated as not found
         self.shipment_subs_repo
-            .save(subs)
+            .find_by_user_and_shipment(user_id, shipment_id)
+            .await
+            .map_err(|e| HttpError::InternalServerError(anyhow::anyhow!(e.to_string())))?
+            .ok_or_else(|| HttpError::NotFound("shipment not found".to_string()))?;
+
+        let limit = query
+            .limit
+            .unwrap_or(DEFAULT_EVENT_PAGE_SIZE)
+            .clamp(1, MAX_PAGE_SIZE);
+
+        // an unknown cursor would otherwise read as the end of the list
+        if let Some(cursor) = query.cursor {
+            let valid = self
+                .tracking_event_repo
+                .belongs_to(cursor, shipment_id)
+                .await
+                .map_err(|e| HttpError::InternalServerError(anyhow::anyhow!(e.to_string())))?;
+
+            if !valid {
+                return Err(HttpError::BadRequest("invalid cursor".to_string()));
+            }
+        }
+
+        // fetch one extra row to know whether there is a next page
+        let mut events = self
+            .tracking_event_repo
+            .find_by_shipment(shipment_id, query.cursor, limit + 1)
             .await
             .map_err(|e| HttpError::InternalServerError(anyhow::anyhow!(e.to_string())))?;
 
-        for ch in req.notify_on.iter() {
-            let recipient = match ch {
-                NotificationChannel::Whatsapp => "6285158824017",
-                NotificationChannel::Email => "akmalmp241@gmail.com",
-                NotificationChannel::Push => "",
-            };
+        let has_more = events.len() as i64 > limit;
+        events.truncate(limit as usize);
 
-            let payload = TrackingEventMsg {
-                message_id: Uuid::new_v4(),
-                event_type: TrackingEventMsgType::TrackingAdded,
-                channel: ch.clone(),
-                user_id: user_uuid,
-                recipient: recipient.to_string(),
-                template_code: "TRACKING_STATUS".to_string(),
-                payload: TrackingMsgPayload {
-                    waybill_id: req.awb.clone(),
-                    status: shipment.current_status.to_string().to_lowercase(),
-                    courier: shipment.courier_code.clone(),
-                },
-            };
+        let next_cursor = match has_more {
+            true => events.last().map(|e| e.id),
+            false => None,
+        };
 
-            let payload = serde_json::to_vec(&payload).map_err(|e| {
-                HttpError::InternalServerError(anyhow!("failed to serialize msg payload"))
-            })?;
+        Ok(ShipmentEventListResponse {
+            data: events.into_iter().map(Into::into).collect(),
+            next_cursor,
+        })
+    }
+
+    /// stores every provider history entry as a tracking event. entries that were
+    /// already stored by a previous poll are skipped, so this is safe to call repeatedly
+    pub async fn record_history(
+        &self,
+        shipment: &Shipment,
+        platform: &str,
+        history: &[TrackingCheckpoint],
+        source: TrackingEventSource,
+    ) -> Result<usize, HttpError> {
+        let mut inserted = 0;
 
-            let sent = self
-                .rabbitmq_channel
-                .basic_publish(
-                    EXCHANGE_NAME,
-                    format!(
-                        "notification.tracking_added.{}",
-                        ch.to_string().to_lowercase()
-                    )
-                    .as_str(),
-                    BasicPublishOptions::default(),
-                    &payload,
-                    BasicProperties::default().with_delivery_mode(2),
-                )
+        for entry in history {
+            let normalized_status = self
+                .map_status_repo
+                .map_external_status(platform, entry.status.as_str())
                 .await
                 .map_err(|e| HttpError::InternalServerError(anyhow::anyhow!(e.to_string())))?;
 
-            sent.await
+            let event = TrackingEvent {
+                id: Uuid::new_v4(),
+                shipment_id: shipment.id,
+                raw_status: entry.status.clone(),
+                normalized_status,
+                description: entry.description.clone(),
+                location: entry.location.clone(),
+                occurred_at: entry.occurred_at,
+                source: source.clone(),
+                created_at: Utc::now(),
+            };
+
+            let is_new = self
+                .tracking_event_repo
+                .save(event)
+                .await
                 .map_err(|e| HttpError::InternalServerError(anyhow::anyhow!(e.to_string())))?;
+
+            if is_new {
+                inserted += 1;
+            }
         }
 
-        let response = AddTrackingResponse {
-            message: "Successfully add new tracking".into(),
-        };
+        if inserted > 0 {
+            tracing::debug!(
+                "stored {} new events for shipment {}",
+                inserted,
+                shipment.id
+            );
+        }
 
-        Ok(response)
+        Ok(inserted)
+    }
+
+    /// publishes freshly committed outbox messages right away, whatever fails here is
+    /// left to the outbox relay
+    async fn publish_pending(&self, outbox: &[OutboxMessage]) {
+        for msg in outbox {
+            if let Err(e) = self.publish_outbox_message(msg).await {
+                tracing::warn!(
+                    "couldn't publish message {}, leaving it to the outbox relay: {}",
+                    msg.id,
+                    e
+                );
+                break;
+            }
+        }
+    }
+
+    /// publishes a queued message and removes it from the outbox
+    pub async fn publish_outbox_message(&self, msg: &OutboxMessage) -> Result<(), HttpError> {
+        self.publish(msg).await?;
+
+        self.outbox_repo
+            .delete(msg.id)
+            .await
+            .map_err(|e| HttpError::InternalServerError(anyhow::anyhow!(e.to_string())))
+    }
+
+    async fn publish(&self, msg: &OutboxMessage) -> Result<(), HttpError> {
+        let properties = BasicProperties::default()
+            .with_delivery_mode(2)
+            .with_content_type("application/json".into())
+            .with_message_id(msg.id.to_string().into())
+            .with_correlation_id(msg.correlation_id.to_string().into())
+            .with_timestamp(Utc::now().timestamp() as u64);
+
+        let payload = serde_json::to_vec(&msg.payload).map_err(|e| {
+            HttpError::InternalServerError(anyhow!("failed to serialize msg payload: {}", e))
+        })?;
+
+        let sent = self
+            .rabbitmq_channel
+            .basic_publish(
+                NOTIFICATION_EXCHANGE,
+                msg.routing_key.as_str(),
+                BasicPublishOptions::default(),
+                &payload,
+                properties,
+            )
+            .await
+            .map_err(|e| HttpError::InternalServerError(anyhow::anyhow!(e.to_string())))?;
+
+        sent.await
+            .map_err(|e| HttpError::InternalServerError(anyhow::anyhow!(e.to_string())))?;
+
+        Ok(())
     }
 }
@@ -0,0 +1,231 @@
+use crate::models::dto::{WebhookFailure, WebhookReplayReport};
+use crate::models::event::{TrackingEvent, TrackingEventSource};
+use crate::models::shipment::ShipmentStatusParse;
+use crate::models::webhook::{WebhookLog, WebhookReplayFilter};
+use crate::repository::webhook_log_repo::WebhookLogRepository;
+use crate::service::tracking_service::TrackingService;
+use biteship::PROVIDER_NAME;
+use biteship::dto::webhook::BiteshipWebhookPayload;
+use biteship::webhook::BiteshipWebhookVerifier;
+use chrono::Utc;
+use errors::error::HttpError;
+use serde_json::Value;
+use uuid::Uuid;
+
+#[derive(Clone)]
+pub struct WebhookService {
+    pub tracking_service: TrackingService,
+    pub webhook_log_repo: WebhookLogRepository,
+    /// None while no signature is configured, the webhook route is left out then
+    pub verifier: Option<BiteshipWebhookVerifier>,
+}
+
+impl WebhookService {
+    pub async fn new(
+        tracking_service: TrackingService,
+        webhook_log_repo: WebhookLogRepository,
+        verifier: Option<BiteshipWebhookVerifier>,
+    ) -> Self {
+        Self {
+            tracking_service,
+            webhook_log_repo,
+            verifier,
+        }
+    }
+
+    /// stores the raw payload first so nothing is lost, then processes it.
+    /// a processing failure leaves `processed_at` empty instead of failing the
+    /// request, biteship would otherwise keep retrying a payload we already have
+    pub async fn receive_biteship(&self, payload: Value) -> Result<WebhookLog, HttpError> {
+        let log = self
+            .webhook_log_repo
+            .save(&payload)
+            .await
+            .map_err(|e| HttpError::InternalServerError(anyhow::anyhow!(e.to_string())))?;
+
+        if let Err(e) = self.process(&log).await {
+            tracing::error!("failed to process webhook {}: {:?}", log.id, e);
+        }
+
+        Ok(log)
+    }
+
+    /// runs the log through the normalization pipeline. on failure the error is stored
+    /// on the row so broken payloads stay visible and can be replayed later
+    pub async fn process(&self, log: &WebhookLog) -> Result<(), HttpError> {
+        let result = match serde_json::from_value::<BiteshipWebhookPayload>(log.payload.clone()) {
+            Ok(payload) => self.apply(log, &payload).await,
+            Err(e) => Err(HttpError::BadRequest(format!(
+                "invalid webhook payload: {}",
+                e
+            ))),
+        };
+
+        let saved = match &result {
+            Ok(_) => self.webhook_log_repo.mark_processed(log.id).await,
+            Err(e) => {
+                self.webhook_log_repo
+                    .record_failure(log.id, describe(e).as_str())
+                    .await
+            }
+        };
+
+        saved.map_err(|e| HttpError::InternalServerError(anyhow::anyhow!(e.to_string())))?;
+
+        result
+    }
+
+    /// replays the given logs one by one, a failing row doesn't stop the others
+    pub async fn replay(&self, logs: Vec<WebhookLog>) -> WebhookReplayReport {
+        let mut report = WebhookReplayReport {
+            total: logs.len(),
+            processed: 0,
+            failed: vec![],
+        };
+
+        for log in logs {
+            match self.process(&log).await {
+                Ok(_) => report.processed += 1,
+                Err(e) => report.failed.push(WebhookFailure {
+                    id: log.id,
+                    error: describe(&e),
+                }),
+            }
+        }
+
+        report
+    }
+
+    /// logs whose processing failed, newest first
+    pub async fn find_failed(&self, limit: i64) -> Result<Vec<WebhookLog>, HttpError> {
+        self.webhook_log_repo
+            .find_failed(limit)
+            .await
+            .map_err(|e| HttpError::InternalServerError(anyhow::anyhow!(e.to_string())))
+    }
+
+    pub async fn replay_filtered(
+        &self,
+        filter: &WebhookReplayFilter,
+        limit: i64,
+    ) -> Result<WebhookReplayReport, HttpError> {
+        let logs = self
+            .webhook_log_repo
+            .find_for_replay(filter, limit)
+            .await
+            .map_err(|e| HttpError::InternalServerError(anyhow::anyhow!(e.to_string())))?;
+
+        Ok(self.replay(logs).await)
+    }
+
+    async fn apply(
+        &self,
+        log: &WebhookLog,
+        payload: &BiteshipWebhookPayload,
+    ) -> Result<(), HttpError> {
+        let (Some(waybill_id), Some(courier_code), Some(raw_status)) = (
+            payload.courier_waybill_id.as_deref(),
+            payload.courier_company.as_deref(),
+            payload.status.as_deref(),
+        ) else {
+            tracing::debug!(
+                "webhook {} ({}) carries no status, skipping",
+                log.id,
+                payload.event
+            );
+            return Ok(());
+        };
+
+        let service = &self.tracking_service;
+
+        let shipment = service
+            .shipment_repository
+            .find_by_waybill(waybill_id, courier_code)
+            .await
+            .map_err(|e| HttpError::InternalServerError(anyhow::anyhow!(e.to_string())))?;
+
+        let Some(shipment) = shipment else {
+            tracing::debug!(
+                "webhook {} is for an untracked waybill {}, skipping",
+                log.id,
+                waybill_id
+            );
+            return Ok(());
+        };
+
+        let normalized_status = service
+            .map_status_repo
+            .map_external_status(PROVIDER_NAME, raw_status)
+            .await
+            .map_err(|e| HttpError::InternalServerError(anyhow::anyhow!(e.to_string())))?;
+
+        let latest = service
+            .tracking_event_repo
+            .find_latest(shipment.id)
+            .await
+            .map_err(|e| HttpError::InternalServerError(anyhow::anyhow!(e.to_string())))?;
+
+        // webhooks can arrive late or out of order, an older update than the history
+        // we already have is only kept as history and must not move the shipment back
+        let stale = match (&latest, payload.updated_at) {
+            (Some(latest), Some(updated_at)) => latest.occurred_at > updated_at,
+            _ => false,
+        };
+
+        // falling back to the log's own timestamp keeps the event
+        // deduplicated when the same payload is processed again
+        let event = TrackingEvent {
+            id: Uuid::new_v4(),
+            shipment_id: shipment.id,
+            raw_status: raw_status.to_string(),
+            normalized_status,
+            description: payload
+                .note
+                .clone()
+                .unwrap_or_else(|| raw_status.to_string()),
+            location: None,
+            occurred_at: payload.updated_at.unwrap_or(log.created_at),
+            source: TrackingEventSource::Webhook,
+            created_at: Utc::now(),
+        };
+
+        service
+            .tracking_event_repo
+            .save(event)
+            .await
+            .map_err(|e| HttpError::InternalServerError(anyhow::anyhow!(e.to_string())))?;
+
+        if shipment.current_status.is_terminal() || stale {
+            tracing::info!(
+                "webhook {} reports {} for shipment {} which is already {}, not applying it",
+                log.id,
+                raw_status,
+                shipment.id,
+                shipment.current_status
+            );
+            return Ok(());
+        }
+
+        let status = service
+            .apply_status(&shipment, PROVIDER_NAME, raw_status)
+            .await?;
+
+        if status.is_terminal() {
+            service
+                .tracking_job_repo
+                .deactivate(shipment.id)
+                .await
+                .map_err(|e| HttpError::InternalServerError(anyhow::anyhow!(e.to_string())))?;
+        }
+
+        Ok(())
+    }
+}
+
+// internal errors only render a generic message, the stored error needs the cause
+fn describe(e: &HttpError) -> String {
+    match e {
+        HttpError::InternalServerError(err) => err.to_string(),
+        other => other.to_string(),
+    }
+}